@@ -1,15 +1,48 @@
 use heck::ToSnakeCase;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, LitStr};
 
-#[proc_macro_derive(Context)]
+/// Options set through `#[context(...)]` attributes.
+#[derive(Default)]
+struct Options {
+    /// The context is only reachable through another context, so no root
+    /// constructor is generated for it.
+    nested: bool,
+    /// The name used in expressions, if it differs from the field name.
+    rename: Option<String>,
+}
+
+fn parse_options(attrs: &[Attribute]) -> syn::Result<Options> {
+    let mut options = Options::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("context")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("nested") {
+                options.nested = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported context attribute"))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
+#[proc_macro_derive(Context, attributes(context))]
 pub fn derive_expr(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = input.ident;
     let ctor_name = struct_name.to_string().to_snake_case();
     let ctor_id = syn::Ident::new(&ctor_name, struct_name.span());
 
+    let options = match parse_options(&input.attrs) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
     // Ensure it's a struct and get its fields
     let fields = if let Data::Struct(data_struct) = input.data {
         if let Fields::Named(fields) = data_struct.fields {
@@ -22,25 +55,40 @@ pub fn derive_expr(input: TokenStream) -> TokenStream {
     };
 
     // Generate methods for each field
-    let methods = fields.named.iter().map(|field| {
+    let mut methods = Vec::new();
+    for field in fields.named.iter() {
         let field_name = &field.ident;
         let field_type = &field.ty;
-        let field_name_str = field_name.as_ref().unwrap().to_string();
-        quote! {
+        let field_options = match parse_options(&field.attrs) {
+            Ok(options) => options,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let field_name_str = field_options
+            .rename
+            .unwrap_or_else(|| field_name.as_ref().unwrap().to_string());
+        methods.push(quote! {
             pub fn #field_name(&self) -> Context<#field_type> {
                 self.select::<#field_type>(#field_name_str)
             }
+        });
+    }
+
+    let ctor = if options.nested {
+        quote! {}
+    } else {
+        quote! {
+            pub fn #ctor_id() -> Self {
+                Self::new().select(#ctor_name)
+            }
         }
-    });
+    };
 
     // Generate the output code
     let expanded = quote! {
         impl Context<#struct_name> {
             #(#methods)*
 
-            pub fn #ctor_id() -> Self {
-                Context::<Github>::new().select(stringify!(#ctor_name))
-            }
+            #ctor
         }
     };
 
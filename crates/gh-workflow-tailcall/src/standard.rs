@@ -7,7 +7,7 @@
 
 use ctx::Context;
 use derive_setters::Setters;
-use generate::Generate;
+use generate::Project;
 use gh_workflow::error::Result;
 use gh_workflow::{Workflow as GHWorkflow, *};
 use heck::ToTitleCase;
@@ -64,13 +64,12 @@ impl Default for StandardWorkflow {
 
 impl StandardWorkflow {
     /// Initialize a job with common configuration including:
-    /// - Permissions
     /// - Setup steps
     /// - Checkout step
     ///
     /// This reduces duplication across different job types.
     fn init_job(&self, name: impl ToString) -> Job {
-        let mut job = Job::new(name).permissions(Permissions::default().contents(Level::Read));
+        let mut job = Job::new(name);
 
         // Add setup steps in reverse order to maintain the correct sequence
         for step in self.setup.iter().rev() {
@@ -98,11 +97,10 @@ impl StandardWorkflow {
 impl StandardWorkflow {
     /// Generates and tests the workflow file.
     pub fn generate(self) -> Result<()> {
-        self.to_ci_workflow().generate()?;
-        Generate::new(self.to_autofix_workflow())
-            .name("autofix.yml")
-            .generate()?;
-        Ok(())
+        Project::new()
+            .add_workflow("ci.yml", self.to_ci_workflow())
+            .add_workflow("autofix.yml", self.to_autofix_workflow())
+            .generate()
     }
 
     /// Converts the workflow into a Github workflow.
@@ -145,8 +143,8 @@ impl StandardWorkflow {
                 "CARGO_REGISTRY_TOKEN",
                 "${{ secrets.CARGO_REGISTRY_TOKEN }}",
             ))
-            .permissions(self.write_permissions())
             .add_step(Release::default().command(cmd))
+            .infer_permissions()
     }
 
     fn lint_job(&self, auto_fix: bool) -> Job {
@@ -193,9 +191,13 @@ impl StandardWorkflow {
             .add_step(clippy_step);
 
         if auto_fix {
-            job = job.add_step(Step::new("auto-fix").uses("autofix-ci", "action", "v1"));
+            job = job.add_step(
+                Step::new("auto-fix")
+                    .uses("autofix-ci", "action", "v1")
+                    .require_permissions(Permissions::default()),
+            );
         }
-        job
+        job.infer_permissions()
     }
 
     /// Creates the "Build and Test" job for the workflow.
@@ -215,6 +217,7 @@ impl StandardWorkflow {
             .add_step(
                 Step::new("Cache Rust dependencies")
                     .uses("Swatinem", "rust-cache", "v2")
+                    .require_permissions(Permissions::default())
                     .add_with(("cache-all-crates", "true")),
             )
             .add_step(match self.test_runner {
@@ -230,14 +233,7 @@ impl StandardWorkflow {
             job = job.add_step(Cargo::new("bench").args("--workspace").name("Cargo Bench"));
         }
 
-        job
-    }
-
-    fn write_permissions(&self) -> Permissions {
-        Permissions::default()
-            .pull_requests(Level::Write)
-            .packages(Level::Write)
-            .contents(Level::Write)
+        job.infer_permissions()
     }
 
     fn workflow_cond(&self) -> Context<bool> {
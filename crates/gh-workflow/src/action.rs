@@ -0,0 +1,252 @@
+//!
+//! The serde representation of a composite action (`action.yml`).
+//! Docs: <https://docs.github.com/en/actions/sharing-automations/creating-actions/metadata-syntax-for-github-actions>
+
+use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::generate::Generate;
+use crate::{Step, StepType, StepValue, YamlOptions};
+
+/// Represents the metadata file of an action.
+#[derive(Debug, Default, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+#[setters(strip_option, into)]
+pub struct Action {
+    /// The name of the action, as displayed in the Actions tab and on the
+    /// marketplace.
+    pub name: String,
+
+    /// The name of the author of the action.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    /// A short description of the action.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The inputs that the action accepts through `with`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[setters(skip)]
+    pub inputs: Option<IndexMap<String, ActionInput>>,
+
+    /// The outputs that the action sets for later steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[setters(skip)]
+    pub outputs: Option<IndexMap<String, ActionOutput>>,
+
+    /// How the action runs.
+    #[setters(skip)]
+    pub runs: ActionRuns,
+
+    /// The icon and color of the action on the marketplace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding: Option<Branding>,
+}
+
+impl Action {
+    /// Creates a new composite `Action` with the specified name.
+    pub fn new<T: ToString>(name: T) -> Self {
+        Self { name: name.to_string(), ..Default::default() }
+    }
+
+    /// Converts the `Action` to a YAML string representation.
+    pub fn to_string(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Converts the `Action` to YAML, formatted with the given options.
+    pub fn to_string_with(&self, options: &YamlOptions) -> Result<String> {
+        crate::yaml::render(self, options)
+    }
+
+    /// Parses a YAML string into an `Action`.
+    pub fn parse(yml: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(yml)?)
+    }
+
+    /// Generates the `action.yml` file in the root of the repository. Use
+    /// [`Generate::action`] to write it to another path.
+    pub fn generate(self) -> Result<()> {
+        Generate::action(self).generate()
+    }
+
+    /// Adds an input to the action.
+    pub fn add_input<S: ToString>(mut self, name: S, input: ActionInput) -> Self {
+        self.inputs
+            .get_or_insert_with(IndexMap::new)
+            .insert(name.to_string(), input);
+        self
+    }
+
+    /// Adds an output to the action.
+    pub fn add_output<S: ToString>(mut self, name: S, output: ActionOutput) -> Self {
+        self.outputs
+            .get_or_insert_with(IndexMap::new)
+            .insert(name.to_string(), output);
+        self
+    }
+
+    /// Adds a step to the composite action.
+    pub fn add_step<S: Into<Step<T>>, T: StepType>(mut self, step: S) -> Self {
+        let step: Step<T> = step.into();
+        match &mut self.runs {
+            ActionRuns::Composite { steps } => steps.push(T::to_value(step)),
+        }
+        self
+    }
+}
+
+/// Represents an input of an action.
+#[derive(Debug, Default, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[setters(strip_option, into)]
+pub struct ActionInput {
+    /// A description of the input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Whether the input must be provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+
+    /// The value used when the input isn't provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+
+    /// A warning shown to users that still provide the input.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecation_message: Option<String>,
+}
+
+impl ActionInput {
+    /// Creates a new `ActionInput` with the specified description.
+    pub fn new<T: ToString>(description: T) -> Self {
+        Self {
+            description: Some(description.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Represents an output of a composite action.
+#[derive(Debug, Default, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct ActionOutput {
+    /// A description of the output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The value of the output, typically the output of one of the steps.
+    pub value: String,
+}
+
+impl ActionOutput {
+    /// Creates a new `ActionOutput` with the specified value.
+    pub fn new<T: ToString>(value: T) -> Self {
+        Self { value: value.to_string(), ..Default::default() }
+    }
+}
+
+/// Represents how an action runs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "using", rename_all = "kebab-case")]
+pub enum ActionRuns {
+    /// Runs the steps of the action in the job that uses it.
+    Composite { steps: Vec<StepValue> },
+}
+
+impl Default for ActionRuns {
+    fn default() -> Self {
+        ActionRuns::Composite { steps: Vec::new() }
+    }
+}
+
+/// Represents the branding of an action on the marketplace.
+#[derive(Debug, Default, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct Branding {
+    /// The name of a Feather icon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+
+    /// The background color of the badge.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<BrandingColor>,
+}
+
+/// Represents the background color of an action's badge.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BrandingColor {
+    White,
+    Black,
+    Yellow,
+    Blue,
+    Green,
+    Orange,
+    Red,
+    Purple,
+    GrayDark,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ctx::Context;
+
+    #[test]
+    fn composite_action() {
+        let mut version = Step::new("Read version")
+            .run("echo version=$(cat VERSION) >> $GITHUB_OUTPUT")
+            .shell("bash");
+        let output = version.output("version");
+
+        let action = Action::new("Read version")
+            .description("Reads the version of the project")
+            .add_input(
+                "path",
+                ActionInput::new("The directory of the project").default("."),
+            )
+            .add_output(
+                "version",
+                ActionOutput::new(output).description("The version"),
+            )
+            .add_step(version.working_directory(Context::inputs().get("path").to_string()))
+            .branding(
+                Branding::default()
+                    .icon("tag")
+                    .color(BrandingColor::GrayDark),
+            );
+
+        let expected = r#"name: Read version
+description: Reads the version of the project
+inputs:
+  path:
+    description: The directory of the project
+    default: .
+outputs:
+  version:
+    description: The version
+    value: ${{ steps.read-version.outputs.version }}
+runs:
+  using: composite
+  steps:
+  - id: read-version
+    name: Read version
+    run: echo version=$(cat VERSION) >> $GITHUB_OUTPUT
+    shell: bash
+    working-directory: ${{ inputs.path }}
+branding:
+  icon: tag
+  color: gray-dark
+"#;
+
+        assert_eq!(action.to_string().unwrap(), expected);
+        assert_eq!(Action::parse(expected).unwrap(), action);
+    }
+}
@@ -0,0 +1,611 @@
+//! Typed representations of the official `actions/*` actions that most
+//! workflows use.
+//! Docs: <https://github.com/actions>
+use derive_setters::Setters;
+use serde_json::Value;
+
+use crate::{Level, Permissions, Step, Use};
+
+/// The inputs of `actions/checkout`.
+/// Docs: <https://github.com/actions/checkout>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct Checkout {
+    /// Repository to check out, as `owner/repo`. (Defaults to the repository
+    /// of the workflow).
+    pub repository: Option<String>,
+
+    /// The branch, tag or SHA to check out. (Defaults to the ref or SHA that
+    /// triggered the workflow).
+    pub ref_: Option<String>,
+
+    /// Token used to fetch the repository. (Defaults to `github.token`).
+    pub token: Option<String>,
+
+    /// Number of commits to fetch. 0 fetches all history for all branches and
+    /// tags. (Defaults to 1).
+    pub fetch_depth: Option<u32>,
+
+    /// Whether to check out submodules. (Defaults to none).
+    pub submodules: Option<Submodules>,
+
+    /// Whether to keep the token in the local git config, so that later steps
+    /// can run authenticated git commands. (Defaults to true).
+    pub persist_credentials: Option<bool>,
+
+    /// Relative path under the workspace to place the repository in.
+    pub path: Option<String>,
+}
+
+/// Which submodules `actions/checkout` checks out.
+#[derive(Clone, Copy)]
+pub enum Submodules {
+    /// Doesn't check out submodules.
+    Disabled,
+
+    /// Checks out the submodules of the repository.
+    Enabled,
+
+    /// Checks out submodules recursively.
+    Recursive,
+}
+
+impl From<Submodules> for Value {
+    fn from(value: Submodules) -> Self {
+        match value {
+            Submodules::Disabled => Value::Bool(false),
+            Submodules::Enabled => Value::Bool(true),
+            Submodules::Recursive => Value::from("recursive"),
+        }
+    }
+}
+
+impl From<Checkout> for Step<Use> {
+    fn from(value: Checkout) -> Self {
+        let mut step = Step::new("Checkout Code")
+            .uses("actions", "checkout", "v5")
+            .require_permissions(Permissions::default().contents(Level::Read));
+
+        if let Some(repository) = value.repository {
+            step = step.add_with(("repository", repository));
+        }
+
+        if let Some(ref_) = value.ref_ {
+            step = step.add_with(("ref", ref_));
+        }
+
+        if let Some(token) = value.token {
+            step = step.add_with(("token", token));
+        }
+
+        if let Some(fetch_depth) = value.fetch_depth {
+            step = step.add_with(("fetch-depth", fetch_depth));
+        }
+
+        if let Some(submodules) = value.submodules {
+            step = step.add_with(("submodules", submodules));
+        }
+
+        if let Some(persist_credentials) = value.persist_credentials {
+            step = step.add_with(("persist-credentials", persist_credentials));
+        }
+
+        if let Some(path) = value.path {
+            step = step.add_with(("path", path));
+        }
+
+        step
+    }
+}
+
+/// The inputs of `actions/cache`.
+/// Docs: <https://github.com/actions/cache>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct Cache {
+    /// The key used to save and restore the cache.
+    pub key: String,
+
+    /// Files, directories and glob patterns to cache.
+    #[setters(skip)]
+    pub paths: Vec<String>,
+
+    /// Key prefixes used to restore a cache when no cache matches `key`, in
+    /// order of preference.
+    #[setters(skip)]
+    pub restore_keys: Vec<String>,
+
+    /// Fails the step when no cache matches `key`. (Defaults to false).
+    pub fail_on_cache_miss: Option<bool>,
+
+    /// Checks whether a cache exists without downloading it. (Defaults to
+    /// false).
+    pub lookup_only: Option<bool>,
+}
+
+impl Cache {
+    /// Creates a new `Cache` with the specified key.
+    pub fn new<S: ToString>(key: S) -> Self {
+        Self { key: key.to_string(), ..Default::default() }
+    }
+
+    /// Adds a path to cache.
+    pub fn add_path<S: ToString>(mut self, path: S) -> Self {
+        self.paths.push(path.to_string());
+        self
+    }
+
+    /// Adds a key prefix to restore from.
+    pub fn add_restore_key<S: ToString>(mut self, key: S) -> Self {
+        self.restore_keys.push(key.to_string());
+        self
+    }
+}
+
+impl From<Cache> for Step<Use> {
+    fn from(value: Cache) -> Self {
+        let mut step = Step::new("Cache")
+            .uses("actions", "cache", "v4")
+            .add_with(("key", value.key));
+
+        if !value.paths.is_empty() {
+            step = step.add_with(("path", value.paths.join("\n")));
+        }
+
+        if !value.restore_keys.is_empty() {
+            step = step.add_with(("restore-keys", value.restore_keys.join("\n")));
+        }
+
+        if let Some(fail_on_cache_miss) = value.fail_on_cache_miss {
+            step = step.add_with(("fail-on-cache-miss", fail_on_cache_miss));
+        }
+
+        if let Some(lookup_only) = value.lookup_only {
+            step = step.add_with(("lookup-only", lookup_only));
+        }
+
+        step
+    }
+}
+
+/// The inputs of `actions/upload-artifact`.
+/// Docs: <https://github.com/actions/upload-artifact>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct UploadArtifact {
+    /// The name of the artifact. (Defaults to `artifact`).
+    pub name: Option<String>,
+
+    /// Files, directories and glob patterns to upload.
+    #[setters(skip)]
+    pub paths: Vec<String>,
+
+    /// What to do when no files match the paths. (Defaults to warn).
+    pub if_no_files_found: Option<IfNoFilesFound>,
+
+    /// Number of days after which the artifact expires. (Defaults to the
+    /// retention of the repository).
+    pub retention_days: Option<u32>,
+
+    /// The zlib compression level, from 0 (none) to 9 (best). (Defaults to 6).
+    pub compression_level: Option<u8>,
+
+    /// Replaces an artifact with the same name instead of failing. (Defaults
+    /// to false).
+    pub overwrite: Option<bool>,
+
+    /// Uploads hidden files too. (Defaults to false).
+    pub include_hidden_files: Option<bool>,
+}
+
+impl UploadArtifact {
+    /// Creates a new `UploadArtifact` with the specified name.
+    pub fn new<S: ToString>(name: S) -> Self {
+        Self { name: Some(name.to_string()), ..Default::default() }
+    }
+
+    /// Adds a path to upload.
+    pub fn add_path<S: ToString>(mut self, path: S) -> Self {
+        self.paths.push(path.to_string());
+        self
+    }
+}
+
+/// What `actions/upload-artifact` does when no files match.
+#[derive(Clone, Copy)]
+pub enum IfNoFilesFound {
+    /// Prints a warning but doesn't fail the step.
+    Warn,
+
+    /// Fails the step.
+    Error,
+
+    /// Doesn't print a warning nor fail the step.
+    Ignore,
+}
+
+impl std::fmt::Display for IfNoFilesFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IfNoFilesFound::Warn => write!(f, "warn"),
+            IfNoFilesFound::Error => write!(f, "error"),
+            IfNoFilesFound::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl From<UploadArtifact> for Step<Use> {
+    fn from(value: UploadArtifact) -> Self {
+        let mut step = Step::new("Upload Artifact").uses("actions", "upload-artifact", "v4");
+
+        if let Some(name) = value.name {
+            step = step.add_with(("name", name));
+        }
+
+        if !value.paths.is_empty() {
+            step = step.add_with(("path", value.paths.join("\n")));
+        }
+
+        if let Some(if_no_files_found) = value.if_no_files_found {
+            step = step.add_with(("if-no-files-found", if_no_files_found.to_string()));
+        }
+
+        if let Some(retention_days) = value.retention_days {
+            step = step.add_with(("retention-days", retention_days));
+        }
+
+        if let Some(compression_level) = value.compression_level {
+            step = step.add_with(("compression-level", compression_level));
+        }
+
+        if let Some(overwrite) = value.overwrite {
+            step = step.add_with(("overwrite", overwrite));
+        }
+
+        if let Some(include_hidden_files) = value.include_hidden_files {
+            step = step.add_with(("include-hidden-files", include_hidden_files));
+        }
+
+        step
+    }
+}
+
+/// The inputs of `actions/download-artifact`.
+/// Docs: <https://github.com/actions/download-artifact>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct DownloadArtifact {
+    /// The name of the artifact. (Defaults to downloading all artifacts of
+    /// the run).
+    pub name: Option<String>,
+
+    /// The directory to download to. (Defaults to the workspace).
+    pub path: Option<String>,
+
+    /// A glob pattern that the names of the artifacts to download must match.
+    /// Ignored when `name` is set.
+    pub pattern: Option<String>,
+
+    /// Downloads all matching artifacts into the same directory, instead of a
+    /// directory per artifact. (Defaults to false).
+    pub merge_multiple: Option<bool>,
+
+    /// Token used to download artifacts of another repository or run.
+    pub github_token: Option<String>,
+
+    /// Repository to download from, as `owner/repo`. Requires `github_token`.
+    pub repository: Option<String>,
+
+    /// The run to download from. Requires `github_token`.
+    pub run_id: Option<String>,
+}
+
+impl DownloadArtifact {
+    /// Creates a new `DownloadArtifact` with the specified name.
+    pub fn new<S: ToString>(name: S) -> Self {
+        Self { name: Some(name.to_string()), ..Default::default() }
+    }
+}
+
+impl From<DownloadArtifact> for Step<Use> {
+    fn from(value: DownloadArtifact) -> Self {
+        let mut step = Step::new("Download Artifact").uses("actions", "download-artifact", "v4");
+
+        if let Some(name) = value.name {
+            step = step.add_with(("name", name));
+        }
+
+        if let Some(path) = value.path {
+            step = step.add_with(("path", path));
+        }
+
+        if let Some(pattern) = value.pattern {
+            step = step.add_with(("pattern", pattern));
+        }
+
+        if let Some(merge_multiple) = value.merge_multiple {
+            step = step.add_with(("merge-multiple", merge_multiple));
+        }
+
+        if let Some(github_token) = value.github_token {
+            step = step.add_with(("github-token", github_token));
+        }
+
+        if let Some(repository) = value.repository {
+            step = step.add_with(("repository", repository));
+        }
+
+        if let Some(run_id) = value.run_id {
+            step = step.add_with(("run-id", run_id));
+        }
+
+        step
+    }
+}
+
+/// The inputs of `actions/setup-node`.
+/// Docs: <https://github.com/actions/setup-node>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct SetupNode {
+    /// Version spec of the Node.js version to use, such as `20`, `lts/*` or
+    /// `>=18.12`.
+    pub node_version: Option<String>,
+
+    /// File containing the version, such as `.nvmrc` or `package.json`.
+    pub node_version_file: Option<String>,
+
+    /// Package manager whose dependencies are cached.
+    pub cache: Option<NodeCache>,
+
+    /// Paths to the dependency files used as the cache key. Supports
+    /// wildcards.
+    #[setters(skip)]
+    pub cache_dependency_paths: Vec<String>,
+
+    /// Registry to set up for authentication, such as
+    /// `https://registry.npmjs.org`.
+    pub registry_url: Option<String>,
+
+    /// Checks for the latest version that satisfies the version spec instead
+    /// of using a cached one. (Defaults to false).
+    pub check_latest: Option<bool>,
+}
+
+impl SetupNode {
+    /// Creates a new `SetupNode` with the specified version.
+    pub fn new<S: ToString>(node_version: S) -> Self {
+        Self {
+            node_version: Some(node_version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a dependency file used as the cache key.
+    pub fn add_cache_dependency_path<S: ToString>(mut self, path: S) -> Self {
+        self.cache_dependency_paths.push(path.to_string());
+        self
+    }
+}
+
+/// Package managers whose dependencies `actions/setup-node` can cache.
+#[derive(Clone, Copy)]
+pub enum NodeCache {
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+impl std::fmt::Display for NodeCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeCache::Npm => write!(f, "npm"),
+            NodeCache::Yarn => write!(f, "yarn"),
+            NodeCache::Pnpm => write!(f, "pnpm"),
+        }
+    }
+}
+
+impl From<SetupNode> for Step<Use> {
+    fn from(value: SetupNode) -> Self {
+        let mut step = Step::new("Setup Node").uses("actions", "setup-node", "v4");
+
+        if let Some(node_version) = value.node_version {
+            step = step.add_with(("node-version", node_version));
+        }
+
+        if let Some(node_version_file) = value.node_version_file {
+            step = step.add_with(("node-version-file", node_version_file));
+        }
+
+        if let Some(cache) = value.cache {
+            step = step.add_with(("cache", cache.to_string()));
+        }
+
+        if !value.cache_dependency_paths.is_empty() {
+            let paths = value.cache_dependency_paths.join("\n");
+            step = step.add_with(("cache-dependency-path", paths));
+        }
+
+        if let Some(registry_url) = value.registry_url {
+            step = step.add_with(("registry-url", registry_url));
+        }
+
+        if let Some(check_latest) = value.check_latest {
+            step = step.add_with(("check-latest", check_latest));
+        }
+
+        step
+    }
+}
+
+/// The inputs of `actions/setup-python`.
+/// Docs: <https://github.com/actions/setup-python>
+#[derive(Clone, Default, Setters)]
+#[setters(strip_option, into)]
+pub struct SetupPython {
+    /// Version spec of the Python version to use, such as `3.12`, `3.x` or
+    /// `pypy3.10`.
+    pub python_version: Option<String>,
+
+    /// File containing the version, such as `.python-version`.
+    pub python_version_file: Option<String>,
+
+    /// Package manager whose dependencies are cached.
+    pub cache: Option<PythonCache>,
+
+    /// Paths to the dependency files used as the cache key. Supports
+    /// wildcards.
+    #[setters(skip)]
+    pub cache_dependency_paths: Vec<String>,
+
+    /// The architecture of the interpreter, such as `x64` or `arm64`.
+    /// (Defaults to the architecture of the runner).
+    pub architecture: Option<String>,
+
+    /// Checks for the latest version that satisfies the version spec instead
+    /// of using a cached one. (Defaults to false).
+    pub check_latest: Option<bool>,
+}
+
+impl SetupPython {
+    /// Creates a new `SetupPython` with the specified version.
+    pub fn new<S: ToString>(python_version: S) -> Self {
+        Self {
+            python_version: Some(python_version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a dependency file used as the cache key.
+    pub fn add_cache_dependency_path<S: ToString>(mut self, path: S) -> Self {
+        self.cache_dependency_paths.push(path.to_string());
+        self
+    }
+}
+
+/// Package managers whose dependencies `actions/setup-python` can cache.
+#[derive(Clone, Copy)]
+pub enum PythonCache {
+    Pip,
+    Pipenv,
+    Poetry,
+}
+
+impl std::fmt::Display for PythonCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PythonCache::Pip => write!(f, "pip"),
+            PythonCache::Pipenv => write!(f, "pipenv"),
+            PythonCache::Poetry => write!(f, "poetry"),
+        }
+    }
+}
+
+impl From<SetupPython> for Step<Use> {
+    fn from(value: SetupPython) -> Self {
+        let mut step = Step::new("Setup Python").uses("actions", "setup-python", "v5");
+
+        if let Some(python_version) = value.python_version {
+            step = step.add_with(("python-version", python_version));
+        }
+
+        if let Some(python_version_file) = value.python_version_file {
+            step = step.add_with(("python-version-file", python_version_file));
+        }
+
+        if let Some(cache) = value.cache {
+            step = step.add_with(("cache", cache.to_string()));
+        }
+
+        if !value.cache_dependency_paths.is_empty() {
+            let paths = value.cache_dependency_paths.join("\n");
+            step = step.add_with(("cache-dependency-path", paths));
+        }
+
+        if let Some(architecture) = value.architecture {
+            step = step.add_with(("architecture", architecture));
+        }
+
+        if let Some(check_latest) = value.check_latest {
+            step = step.add_with(("check-latest", check_latest));
+        }
+
+        step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn official_actions() {
+        let steps: Vec<Step<Use>> = vec![
+            Checkout::default()
+                .fetch_depth(0u32)
+                .ref_("main")
+                .submodules(Submodules::Recursive)
+                .persist_credentials(false)
+                .into(),
+            Cache::new("deps-${{ hashFiles('**/Cargo.lock') }}")
+                .add_path("~/.cargo/registry")
+                .add_path("target")
+                .add_restore_key("deps-")
+                .into(),
+            SetupNode::new("20").cache(NodeCache::Npm).into(),
+            SetupPython::new("3.12").cache(PythonCache::Pip).into(),
+            UploadArtifact::new("dist")
+                .add_path("dist/")
+                .if_no_files_found(IfNoFilesFound::Error)
+                .retention_days(5u32)
+                .into(),
+            DownloadArtifact::new("dist").path("out").into(),
+        ];
+        let steps: Vec<_> = steps.into_iter().map(|step| step.value).collect();
+
+        assert_eq!(
+            serde_yaml::to_string(&steps).unwrap(),
+            r#"- name: Checkout Code
+  uses: actions/checkout@v5
+  with:
+    ref: main
+    fetch-depth: 0
+    submodules: recursive
+    persist-credentials: false
+- name: Cache
+  uses: actions/cache@v4
+  with:
+    key: deps-${{ hashFiles('**/Cargo.lock') }}
+    path: |-
+      ~/.cargo/registry
+      target
+    restore-keys: deps-
+- name: Setup Node
+  uses: actions/setup-node@v4
+  with:
+    node-version: '20'
+    cache: npm
+- name: Setup Python
+  uses: actions/setup-python@v5
+  with:
+    python-version: '3.12'
+    cache: pip
+- name: Upload Artifact
+  uses: actions/upload-artifact@v4
+  with:
+    name: dist
+    path: dist/
+    if-no-files-found: error
+    retention-days: 5
+- name: Download Artifact
+  uses: actions/download-artifact@v4
+  with:
+    name: dist
+    path: out
+"#
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use derive_setters::Setters;
 
 use crate::toolchain::Version;
-use crate::{Run, Step};
+use crate::{Permissions, Run, Step};
 
 #[derive(Clone, Setters)]
 #[setters(strip_option, into)]
@@ -79,7 +79,9 @@ impl From<Cargo> for Step<Run> {
                 .filter(|arg| !arg.is_empty()),
         );
 
-        let mut step = Step::new(format!("Cargo {}", value.command)).run(command.join(" "));
+        let mut step = Step::new(format!("Cargo {}", value.command))
+            .run(command.join(" "))
+            .require_permissions(Permissions::default());
 
         if let Some(id) = value.id {
             step = step.id(id);
@@ -22,31 +22,40 @@ enum Step {
         name: Rc<String>,
         object: Box<Step>,
     },
-    Eq {
-        left: Box<Step>,
-        right: Box<Step>,
-    },
-    And {
-        left: Box<Step>,
-        right: Box<Step>,
-    },
-    Or {
+    Binary {
+        operator: Operator,
         left: Box<Step>,
         right: Box<Step>,
     },
+    Not(Box<Step>),
     Literal(String),
-    Concat {
-        left: Box<Step>,
-        right: Box<Step>,
+    Bool(bool),
+    Number(String),
+    Concat(Vec<Step>),
+    Call {
+        function: &'static str,
+        args: Vec<Step>,
     },
 }
 
+#[derive(Clone, Copy)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
 impl<A> Context<A> {
     fn new() -> Self {
         Context { marker: PhantomData, step: Step::Root }
     }
 
-    fn select<B>(&self, path: impl Into<String>) -> Context<B> {
+    pub(crate) fn select<B>(&self, path: impl Into<String>) -> Context<B> {
         Context {
             marker: PhantomData,
             step: Step::Select {
@@ -56,46 +65,153 @@ impl<A> Context<A> {
         }
     }
 
-    pub fn eq(&self, other: Context<A>) -> Context<bool> {
+    fn binary(&self, operator: Operator, other: Context<A>) -> Context<bool> {
         Context {
             marker: Default::default(),
-            step: Step::Eq {
+            step: Step::Binary {
+                operator,
                 left: Box::new(self.step.clone()),
-                right: Box::new(other.step.clone()),
+                right: Box::new(other.step),
             },
         }
     }
 
+    pub fn eq(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Eq, other)
+    }
+
+    pub fn ne(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Ne, other)
+    }
+
+    pub fn lt(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Lt, other)
+    }
+
+    pub fn le(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Le, other)
+    }
+
+    pub fn gt(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Gt, other)
+    }
+
+    pub fn ge(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Ge, other)
+    }
+
     pub fn and(&self, other: Context<A>) -> Context<bool> {
-        Context {
-            marker: Default::default(),
-            step: Step::And {
-                left: Box::new(self.step.clone()),
-                right: Box::new(other.step.clone()),
-            },
-        }
+        self.binary(Operator::And, other)
     }
 
     pub fn or(&self, other: Context<A>) -> Context<bool> {
+        self.binary(Operator::Or, other)
+    }
+
+    pub fn not(&self) -> Context<bool> {
         Context {
             marker: Default::default(),
-            step: Step::Or {
-                left: Box::new(self.step.clone()),
-                right: Box::new(other.step.clone()),
-            },
+            step: Step::Not(Box::new(self.step.clone())),
         }
     }
 }
 
+impl<A> Context<A> {
+    fn call<B>(function: &'static str, args: Vec<Step>) -> Context<B> {
+        Context { marker: PhantomData, step: Step::Call { function, args } }
+    }
+
+    /// Checks if `self` contains `item`. Works with strings, where it looks
+    /// for a substring, and with arrays, where it looks for an element.
+    pub fn contains<B>(&self, item: Context<B>) -> Context<bool> {
+        Self::call("contains", vec![self.step.clone(), item.step])
+    }
+
+    /// Concatenates the values of an array into a string, separated by
+    /// `separator`.
+    pub fn join(&self, separator: Context<String>) -> Context<String> {
+        Self::call("join", vec![self.step.clone(), separator.step])
+    }
+
+    /// Returns a pretty-print JSON representation of the value.
+    pub fn to_json(&self) -> Context<String> {
+        Self::call("toJSON", vec![self.step.clone()])
+    }
+}
+
 impl Context<String> {
+    /// Concatenates two strings. Since expressions have no concatenation
+    /// operator, the result is rendered as a call to `format`.
     pub fn concat(&self, other: Context<String>) -> Context<String> {
-        Context {
-            marker: Default::default(),
-            step: Step::Concat {
-                left: Box::new(self.step.clone()),
-                right: Box::new(other.step),
-            },
+        let mut parts = match &self.step {
+            Step::Concat(parts) => parts.clone(),
+            step => vec![step.clone()],
+        };
+        match other.step {
+            Step::Concat(other) => parts.extend(other),
+            step => parts.push(step),
         }
+
+        Context { marker: Default::default(), step: Step::Concat(parts) }
+    }
+
+    /// Checks if the string starts with `value`.
+    pub fn starts_with(&self, value: Context<String>) -> Context<bool> {
+        Self::call("startsWith", vec![self.step.clone(), value.step])
+    }
+
+    /// Checks if the string ends with `value`.
+    pub fn ends_with(&self, value: Context<String>) -> Context<bool> {
+        Self::call("endsWith", vec![self.step.clone(), value.step])
+    }
+
+    /// Parses the string as JSON, for example to build a matrix from the
+    /// output of another job.
+    pub fn from_json<B>(&self) -> Context<B> {
+        Self::call("fromJSON", vec![self.step.clone()])
+    }
+
+    /// Replaces the `{0}`, `{1}`, ... placeholders of `template` with `args`.
+    pub fn format<A>(
+        template: impl Into<String>,
+        args: impl IntoIterator<Item = Context<A>>,
+    ) -> Context<String> {
+        let mut steps = vec![Step::Literal(template.into())];
+        steps.extend(args.into_iter().map(|arg| arg.step));
+        Self::call("format", steps)
+    }
+
+    /// Returns a hash of the files in the repository matching `patterns`.
+    pub fn hash_files<S: ToString>(patterns: impl IntoIterator<Item = S>) -> Context<String> {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| Step::Literal(pattern.to_string()))
+            .collect();
+        Self::call("hashFiles", patterns)
+    }
+}
+
+impl Context<bool> {
+    /// Returns `true` when none of the previous steps have failed or been
+    /// canceled.
+    pub fn success() -> Context<bool> {
+        Self::call("success", vec![])
+    }
+
+    /// Returns `true` when any previous step of a job fails, or when any
+    /// ancestor job fails.
+    pub fn failure() -> Context<bool> {
+        Self::call("failure", vec![])
+    }
+
+    /// Returns `true` if the workflow was canceled.
+    pub fn cancelled() -> Context<bool> {
+        Self::call("cancelled", vec![])
+    }
+
+    /// Always returns `true`, even when the workflow is canceled.
+    pub fn always() -> Context<bool> {
+        Self::call("always", vec![])
     }
 }
 
@@ -196,6 +312,55 @@ impl Context<Github> {
     }
 }
 
+impl Operator {
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Eq | Operator::Ne => 3,
+            Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => 4,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = match self {
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::And => "&&",
+            Operator::Or => "||",
+        };
+        write!(f, "{operator}")
+    }
+}
+
+impl Step {
+    /// Binding strength of the step, following the operator precedence of
+    /// GitHub expressions. Higher values bind tighter.
+    fn precedence(&self) -> u8 {
+        match self {
+            Step::Binary { operator, .. } => operator.precedence(),
+            Step::Not(_) => 5,
+            _ => 6,
+        }
+    }
+
+    /// Writes `step`, wrapping it in parentheses when it binds looser than
+    /// `precedence`.
+    fn fmt_operand(f: &mut fmt::Formatter<'_>, step: &Step, precedence: u8) -> fmt::Result {
+        if step.precedence() < precedence {
+            write!(f, "({step})")
+        } else {
+            write!(f, "{step}")
+        }
+    }
+}
+
 impl fmt::Display for Step {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -204,23 +369,61 @@ impl fmt::Display for Step {
                 if matches!(**object, Step::Root) {
                     write!(f, "{name}")
                 } else {
-                    write!(f, "{object}.{name}")
+                    Step::fmt_operand(f, object, self.precedence())?;
+                    write!(f, ".{name}")
                 }
             }
-            Step::Eq { left, right } => {
-                write!(f, "{left} == {right}")
-            }
-            Step::And { left, right } => {
-                write!(f, "{left} && {right}")
+            Step::Binary { operator, left, right } => {
+                // Operators are left-associative, so a right operand of the
+                // same precedence needs parentheses to keep its grouping.
+                let precedence = operator.precedence();
+                Step::fmt_operand(f, left, precedence)?;
+                write!(f, " {operator} ")?;
+                Step::fmt_operand(f, right, precedence + 1)
             }
-            Step::Or { left, right } => {
-                write!(f, "{left} || {right}")
+            Step::Not(step) => {
+                write!(f, "!")?;
+                Step::fmt_operand(f, step, self.precedence())
             }
             Step::Literal(value) => {
-                write!(f, "'{value}'")
+                write!(f, "'{}'", value.replace('\'', "''"))
+            }
+            Step::Bool(value) => {
+                write!(f, "{value}")
+            }
+            Step::Number(value) => {
+                write!(f, "{value}")
+            }
+            Step::Concat(parts) => {
+                // Literal parts are inlined in the format string, escaping
+                // braces so they aren't mistaken for placeholders.
+                let mut template = String::new();
+                let mut args = Vec::new();
+                for part in parts {
+                    match part {
+                        Step::Literal(value) => {
+                            template.push_str(&value.replace('{', "{{").replace('}', "}}"))
+                        }
+                        part => {
+                            template.push_str(&format!("{{{}}}", args.len()));
+                            args.push(part.clone());
+                        }
+                    }
+                }
+
+                let mut steps = vec![Step::Literal(template)];
+                steps.extend(args);
+                write!(f, "{}", Step::Call { function: "format", args: steps })
             }
-            Step::Concat { left, right } => {
-                write!(f, "{left}{right}")
+            Step::Call { function, args } => {
+                write!(f, "{function}(")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{arg}")?;
+                }
+                write!(f, ")")
             }
         }
     }
@@ -228,7 +431,7 @@ impl fmt::Display for Step {
 
 impl<A> fmt::Display for Context<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "${{{{ {} }}}}", self.step.to_string().replace('"', ""))
+        write!(f, "${{{{ {} }}}}", self.step)
     }
 }
 
@@ -247,9 +450,47 @@ impl<T: Into<String>> From<T> for Context<String> {
     }
 }
 
+impl From<bool> for Context<bool> {
+    fn from(value: bool) -> Self {
+        Context { marker: Default::default(), step: Step::Bool(value) }
+    }
+}
+
+macro_rules! number_literal {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Context<$ty> {
+                fn from(value: $ty) -> Self {
+                    Context { marker: Default::default(), step: Step::Number(value.to_string()) }
+                }
+            }
+        )*
+    };
+}
+
+number_literal!(i32, i64, u32, u64);
+
+impl From<f64> for Context<f64> {
+    fn from(value: f64) -> Self {
+        let value = if value.is_infinite() {
+            if value.is_sign_positive() {
+                "Infinity"
+            } else {
+                "-Infinity"
+            }
+            .to_string()
+        } else {
+            value.to_string()
+        };
+        Context { marker: Default::default(), step: Step::Number(value) }
+    }
+}
+
 #[allow(unused)]
 #[derive(Context)]
 /// The job context contains information about the currently running job.
+/// Since `github.job` has the same name, create it with
+/// `Context::<Job>::job()`.
 pub struct Job {
     /// A unique number for each container in a job. This property is only
     /// available if the job uses a container.
@@ -264,7 +505,7 @@ pub struct Job {
 }
 
 /// The status of a job execution
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JobStatus {
     /// The job completed successfully
     Success,
@@ -275,6 +516,7 @@ pub enum JobStatus {
 }
 
 #[derive(Context)]
+#[context(nested)]
 #[allow(unused)]
 /// Container information for a job. This is only available if the job runs in a
 /// container.
@@ -286,16 +528,200 @@ pub struct Container {
 }
 
 #[derive(Context)]
-
+#[context(nested)]
 /// Services configured for a job. This is only available if the job uses
 /// service containers.
 pub struct Services {}
 
+#[allow(unused)]
+#[derive(Context)]
+/// The runner context contains information about the runner that is executing
+/// the current job.
+pub struct Runner {
+    /// The name of the runner executing the job.
+    name: String,
+    /// The operating system of the runner executing the job. Possible values
+    /// are Linux, Windows, or macOS.
+    os: String,
+    /// The architecture of the runner executing the job. Possible values are
+    /// X86, X64, ARM, or ARM64.
+    arch: String,
+    /// The path to a temporary directory on the runner.
+    temp: String,
+    /// The path to the directory containing preinstalled tools for
+    /// GitHub-hosted runners.
+    tool_cache: String,
+    /// This is set only if debug logging is enabled, and always has the value
+    /// of 1.
+    debug: String,
+    /// The environment of the runner executing the job. Possible values are
+    /// github-hosted or self-hosted.
+    environment: String,
+}
+
+#[allow(unused)]
+#[derive(Context)]
+/// The strategy context contains information about the matrix execution
+/// strategy for the current job.
+pub struct Strategy {
+    /// Whether all in-progress jobs are canceled if any job in the matrix
+    /// fails.
+    #[context(rename = "fail-fast")]
+    fail_fast: bool,
+    /// The index of the current job in the matrix, starting at zero.
+    #[context(rename = "job-index")]
+    job_index: u32,
+    /// The total number of jobs in the matrix.
+    #[context(rename = "job-total")]
+    job_total: u32,
+    /// The maximum number of jobs that can run simultaneously.
+    #[context(rename = "max-parallel")]
+    max_parallel: u32,
+}
+
+#[derive(Context)]
+/// The env context contains variables that have been set in a workflow, job,
+/// or step. Since `github.env` has the same name, create it with
+/// `Context::<Env>::env()`.
+pub struct Env {}
+
+impl Context<Env> {
+    /// The value of the environment variable `name`.
+    pub fn get(&self, name: impl Into<String>) -> Context<String> {
+        self.select(name)
+    }
+}
+
+#[derive(Context)]
+/// The vars context contains custom configuration variables set at the
+/// organization, repository, and environment levels.
+pub struct Vars {}
+
+impl Context<Vars> {
+    /// The value of the configuration variable `name`.
+    pub fn get(&self, name: impl Into<String>) -> Context<String> {
+        self.select(name)
+    }
+}
+
+#[derive(Context)]
+/// The secrets context contains the names and values of secrets that are
+/// available to a workflow run.
+pub struct Secrets {}
+
+impl Context<Secrets> {
+    /// The value of the secret `name`.
+    pub fn get(&self, name: impl Into<String>) -> Context<String> {
+        self.select(name)
+    }
+
+    /// The automatically created token for each workflow run.
+    pub fn github_token(&self) -> Context<String> {
+        self.select("GITHUB_TOKEN")
+    }
+}
+
+#[derive(Context)]
+/// The inputs context contains the inputs passed to an action, to a reusable
+/// workflow, or to a manually triggered workflow.
+pub struct Inputs {}
+
+impl Context<Inputs> {
+    /// The value of the input `name`.
+    pub fn get(&self, name: impl Into<String>) -> Context<String> {
+        self.select(name)
+    }
+}
+
+#[derive(Context)]
+/// The matrix context contains the matrix properties defined in the workflow
+/// that apply to the current job.
+pub struct Matrix {}
+
+impl Context<Matrix> {
+    /// The value of the matrix property `key` for the current job.
+    pub fn get(&self, key: impl Into<String>) -> Context<String> {
+        self.select(key)
+    }
+}
+
+#[derive(Context)]
+/// The needs context contains outputs from all jobs that are defined as a
+/// direct dependency of the current job.
+pub struct Needs {}
+
+impl Context<Needs> {
+    /// The job with the id `job_id` that the current job depends on.
+    pub fn get(&self, job_id: impl Into<String>) -> Context<NeededJob> {
+        self.select(job_id)
+    }
+}
+
+#[allow(unused)]
+#[derive(Context)]
+#[context(nested)]
+/// A job that the current job depends on.
+pub struct NeededJob {
+    /// The set of outputs of the job.
+    outputs: Outputs,
+    /// The result of the job. Possible values are success, failure, cancelled,
+    /// or skipped.
+    result: String,
+}
+
+#[derive(Context)]
+/// The steps context contains information about the steps in the current job
+/// that have an id specified and have already run.
+pub struct Steps {}
+
+impl Context<Steps> {
+    /// The step with the id `step_id`.
+    pub fn get(&self, step_id: impl Into<String>) -> Context<PreviousStep> {
+        self.select(step_id)
+    }
+}
+
+#[allow(unused)]
+#[derive(Context)]
+#[context(nested)]
+/// A step of the current job that has already run.
+pub struct PreviousStep {
+    /// The set of outputs defined for the step.
+    outputs: Outputs,
+    /// The result of the step after `continue-on-error` is applied.
+    conclusion: String,
+    /// The result of the step before `continue-on-error` is applied.
+    outcome: String,
+}
+
+#[derive(Context)]
+#[context(nested)]
+/// The outputs of a job or of a step.
+pub struct Outputs {}
+
+impl Context<Outputs> {
+    /// The value of the output `name`.
+    pub fn get(&self, name: impl Into<String>) -> Context<String> {
+        self.select(name)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::eval::Ast;
+
+    /// Parses a rendered expression back, so the tests can check that it is
+    /// well-formed and that literals survive the round-trip.
+    fn parse(input: &str) -> Ast {
+        let input = input
+            .strip_prefix("${{ ")
+            .and_then(|input| input.strip_suffix(" }}"))
+            .unwrap_or_else(|| panic!("missing expression delimiters: {input}"));
+        Ast::parse(input).unwrap()
+    }
 
     #[test]
     fn test_expr() {
@@ -350,4 +776,240 @@ mod test {
             "${{ github.action == github.action_path || github.action == github.action_ref }}"
         );
     }
+
+    #[test]
+    fn test_expr_functions() {
+        let github = Context::github();
+
+        let expr = github.ref_().starts_with("refs/tags/".into());
+        assert_eq!(
+            expr.to_string(),
+            "${{ startsWith(github.ref, 'refs/tags/') }}"
+        );
+
+        let expr = github.head_ref().ends_with("-rc".into());
+        assert_eq!(expr.to_string(), "${{ endsWith(github.head_ref, '-rc') }}");
+
+        let expr = github.event_name().contains(Context::from("pull"));
+        assert_eq!(
+            expr.to_string(),
+            "${{ contains(github.event_name, 'pull') }}"
+        );
+
+        let expr = Context::format("{0}-{1}", [github.workflow(), github.ref_()]);
+        assert_eq!(
+            expr.to_string(),
+            "${{ format('{0}-{1}', github.workflow, github.ref) }}"
+        );
+
+        let expr = github.event().to_json();
+        assert_eq!(expr.to_string(), "${{ toJSON(github.event) }}");
+
+        let expr: Context<serde_json::Value> = github.sha().from_json();
+        assert_eq!(
+            expr.join(", ".into()).to_string(),
+            "${{ join(fromJSON(github.sha), ', ') }}"
+        );
+
+        let expr = Context::hash_files(["**/Cargo.lock", "**/Cargo.toml"]);
+        assert_eq!(
+            expr.to_string(),
+            "${{ hashFiles('**/Cargo.lock', '**/Cargo.toml') }}"
+        );
+    }
+
+    #[test]
+    fn test_expr_status_functions() {
+        assert_eq!(Context::success().to_string(), "${{ success() }}");
+        assert_eq!(Context::failure().to_string(), "${{ failure() }}");
+        assert_eq!(Context::cancelled().to_string(), "${{ cancelled() }}");
+
+        let expr = Context::always().and(Context::github().ref_protected());
+        assert_eq!(expr.to_string(), "${{ always() && github.ref_protected }}");
+    }
+
+    #[test]
+    fn test_expr_precedence() {
+        let github = Context::github();
+        let push = github.event_name().eq("push".into());
+        let tag = github.ref_type().eq("tag".into());
+        let main = github.ref_().eq("refs/heads/main".into());
+
+        let expr = push.or(tag.clone()).and(main.clone());
+        assert_eq!(
+            expr.to_string(),
+            "${{ (github.event_name == 'push' || github.ref_type == 'tag') && github.ref == 'refs/heads/main' }}"
+        );
+
+        let expr = push.or(tag.and(main));
+        assert_eq!(
+            expr.to_string(),
+            "${{ github.event_name == 'push' || github.ref_type == 'tag' && github.ref == 'refs/heads/main' }}"
+        );
+
+        let expr = push.and(Context::success().or(Context::failure())).not();
+        assert_eq!(
+            expr.to_string(),
+            "${{ !(github.event_name == 'push' && (success() || failure())) }}"
+        );
+    }
+
+    #[test]
+    fn test_expr_comparisons() {
+        let github = Context::github();
+
+        let expr = github.ref_protected().eq(true.into());
+        assert_eq!(expr.to_string(), "${{ github.ref_protected == true }}");
+
+        let expr = github.event_name().ne("push".into()).not();
+        assert_eq!(expr.to_string(), "${{ !(github.event_name != 'push') }}");
+
+        let expr = Context::success().not();
+        assert_eq!(expr.to_string(), "${{ !success() }}");
+
+        let attempt: Context<u32> = 3.into();
+        let expr = attempt.lt(1.into()).or(attempt.ge(10.into()));
+        assert_eq!(expr.to_string(), "${{ 3 < 1 || 3 >= 10 }}");
+
+        let ratio: Context<f64> = 0.5.into();
+        let expr = ratio.le(f64::INFINITY.into()).and(ratio.gt((-1.5).into()));
+        assert_eq!(expr.to_string(), "${{ 0.5 <= Infinity && 0.5 > -1.5 }}");
+    }
+
+    #[test]
+    fn test_expr_literal_escaping() {
+        let message = "it's a \"quoted\" {value}";
+        let expr = Context::github().head_ref().eq(message.into());
+
+        assert_eq!(
+            expr.to_string(),
+            "${{ github.head_ref == 'it''s a \"quoted\" {value}' }}"
+        );
+        assert_eq!(
+            parse(&expr.to_string()),
+            Ast::Binary(
+                "==".into(),
+                Box::new(Ast::Path(vec!["github".into(), "head_ref".into()])),
+                Box::new(Ast::String(message.into()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_expr_concat() {
+        let github = Context::github();
+        let expr = Context::from("release-{")
+            .concat(github.ref_())
+            .concat("-'x'".into());
+
+        assert_eq!(
+            expr.to_string(),
+            "${{ format('release-{{{0}-''x''', github.ref) }}"
+        );
+        assert_eq!(
+            parse(&expr.to_string()),
+            Ast::Call(
+                "format".into(),
+                vec![
+                    Ast::String("release-{{{0}-'x'".into()),
+                    Ast::Path(vec!["github".into(), "ref".into()]),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_expr_grammar() {
+        let github = Context::github();
+        let tag = github.ref_().starts_with("refs/tags/".into());
+        let attempt: Context<u32> = 2.into();
+        let expressions = [
+            github.to_string(),
+            Context::<Job>::job().status().to_string(),
+            github
+                .event_name()
+                .eq("push".into())
+                .or(tag.clone())
+                .and(Context::always())
+                .to_string(),
+            tag.not().and(attempt.ge(1.into()).not()).to_string(),
+            Context::format("{0}", [github.sha(), github.ref_()]).to_string(),
+            Context::hash_files(["it's/**"]).to_string(),
+            github
+                .event()
+                .to_json()
+                .from_json::<String>()
+                .contains(Context::from("'"))
+                .to_string(),
+            Context::from("a")
+                .concat(github.actor())
+                .concat("}".into())
+                .to_string(),
+        ];
+
+        for expr in expressions {
+            parse(&expr);
+        }
+    }
+
+    #[test]
+    fn test_expr_contexts() {
+        assert_eq!(Context::runner().os().to_string(), "${{ runner.os }}");
+        assert_eq!(
+            Context::strategy().job_index().to_string(),
+            "${{ strategy.job-index }}"
+        );
+        assert_eq!(
+            Context::secrets().github_token().to_string(),
+            "${{ secrets.GITHUB_TOKEN }}"
+        );
+        assert_eq!(
+            Context::secrets().get("NPM_TOKEN").to_string(),
+            "${{ secrets.NPM_TOKEN }}"
+        );
+        assert_eq!(
+            Context::vars().get("REGION").to_string(),
+            "${{ vars.REGION }}"
+        );
+        assert_eq!(
+            Context::<Env>::env().get("HOME").to_string(),
+            "${{ env.HOME }}"
+        );
+        assert_eq!(
+            Context::inputs().get("tag").to_string(),
+            "${{ inputs.tag }}"
+        );
+        assert_eq!(Context::matrix().get("os").to_string(), "${{ matrix.os }}");
+        assert_eq!(
+            Context::needs()
+                .get("build")
+                .outputs()
+                .get("version")
+                .to_string(),
+            "${{ needs.build.outputs.version }}"
+        );
+        assert_eq!(
+            Context::steps()
+                .get("meta")
+                .outputs()
+                .get("tags")
+                .to_string(),
+            "${{ steps.meta.outputs.tags }}"
+        );
+        assert_eq!(
+            Context::<Job>::job().container().id().to_string(),
+            "${{ job.container.id }}"
+        );
+        assert_eq!(Context::github().job().to_string(), "${{ github.job }}");
+        assert_eq!(Context::github().env().to_string(), "${{ github.env }}");
+
+        let expr = Context::runner()
+            .os()
+            .eq("Linux".into())
+            .and(Context::needs().get("build").result().eq("success".into()));
+        assert_eq!(
+            expr.to_string(),
+            "${{ runner.os == 'Linux' && needs.build.result == 'success' }}"
+        );
+    }
 }
@@ -7,6 +7,8 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::ctx::Context;
+
 /// Represents environment variables in the workflow.
 #[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(transparent)]
@@ -25,7 +27,7 @@ impl Env {
         let mut map = IndexMap::new();
         map.insert(
             "GITHUB_TOKEN".to_string(),
-            Value::from("${{ secrets.GITHUB_TOKEN }}"),
+            Value::from(Context::secrets().github_token().to_string()),
         );
         Env(map)
     }
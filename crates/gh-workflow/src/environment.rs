@@ -4,6 +4,8 @@
 use derive_setters::Setters;
 use serde::{Deserialize, Serialize};
 
+use crate::Expression;
+
 /// Represents an environment for jobs.
 #[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -12,7 +14,64 @@ pub struct Environment {
     /// The name of the environment.
     pub name: String,
 
-    /// The URL associated with the environment.
+    /// The URL associated with the environment. It can be a literal URL or an
+    /// expression such as `${{ steps.deploy.outputs.url }}`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
+    pub url: Option<Expression>,
+}
+
+impl Environment {
+    /// Creates a new `Environment` with the specified name.
+    pub fn new<S: ToString>(name: S) -> Self {
+        Self { name: name.to_string(), url: None }
+    }
+}
+
+/// Represents the environment that a job references, either just by its name
+/// or with the full environment configuration.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum JobEnvironment {
+    /// The name of the environment.
+    Name(String),
+
+    /// The name of the environment along with its URL.
+    Environment(Environment),
+}
+
+impl From<&str> for JobEnvironment {
+    fn from(value: &str) -> Self {
+        JobEnvironment::Name(value.to_string())
+    }
+}
+
+impl From<String> for JobEnvironment {
+    fn from(value: String) -> Self {
+        JobEnvironment::Name(value)
+    }
+}
+
+impl From<Environment> for JobEnvironment {
+    fn from(value: Environment) -> Self {
+        JobEnvironment::Environment(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ctx::Context;
+
+    #[test]
+    fn environment_url_from_context() {
+        let url = Context::github().server_url();
+        let environment: JobEnvironment = Environment::new("docs").url(url).into();
+
+        assert_eq!(
+            serde_yaml::to_string(&environment).unwrap(),
+            "name: docs\nurl: ${{ github.server_url }}\n"
+        );
+    }
 }
@@ -5,11 +5,32 @@
 pub enum Error {
     IO(std::io::Error),
     Yaml(serde_yaml::Error),
-    GitHubWorkflowMismatch,
-    JobIdAlreadyExists(String),
     UTF8(std::string::FromUtf8Error),
-    OutdatedWorkflow,
+    #[from(ignore)]
+    OutdatedWorkflow {
+        path: std::path::PathBuf,
+        diff: String,
+    },
     MissingWorkflowFile(std::path::PathBuf),
+    #[from(ignore)]
+    StaleWorkflowFiles(Vec<std::path::PathBuf>),
+    #[from(ignore)]
+    InvalidWorkflow(Vec<crate::Diagnostic>),
+    #[from(ignore)]
+    UnknownMatrixAxis(String),
+    #[from(ignore)]
+    MissingLockFile(std::path::PathBuf),
+    #[from(ignore)]
+    InvalidActionPin {
+        action: String,
+        sha: String,
+    },
+    #[from(ignore)]
+    UnpinnedActions(Vec<String>),
+    #[from(ignore)]
+    InvalidExpression(String),
+    #[from(ignore)]
+    ConflictingOutput(std::path::PathBuf),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
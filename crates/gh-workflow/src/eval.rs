@@ -0,0 +1,918 @@
+//!
+//! Evaluation of expressions against mock contexts, to test conditions and
+//! values locally.
+//! See: <https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/evaluate-expressions-in-workflows-and-actions>
+
+use std::cmp::Ordering;
+
+use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::ctx::JobStatus;
+use crate::error::{Error, Result};
+use crate::Expression;
+
+/// Contexts to evaluate expressions against, such as `github`, `env` or
+/// `matrix`, given as a JSON document with one property per context:
+///
+/// ```
+/// # use gh_workflow::ctx::Context;
+/// # use gh_workflow::MockContext;
+/// let tag = Context::github().ref_().starts_with("refs/tags/".into());
+/// let push = MockContext::new(serde_json::json!({
+///     "github": { "event_name": "push", "ref": "refs/tags/v1.0.0" }
+/// }));
+///
+/// assert!(push.condition(tag).unwrap());
+/// ```
+#[derive(Debug, Clone, Setters)]
+#[setters(into)]
+pub struct MockContext {
+    #[setters(skip)]
+    contexts: Value,
+
+    /// The status of the job, which the status functions such as
+    /// `success()` check. Defaults to [`JobStatus::Success`].
+    status: JobStatus,
+}
+
+impl MockContext {
+    /// Creates a new `MockContext` from a JSON object whose properties are
+    /// the contexts.
+    pub fn new(contexts: Value) -> Self {
+        Self { contexts, status: JobStatus::Success }
+    }
+
+    /// Evaluates an expression, either wrapped in `${{ }}` or not. Text
+    /// around or between `${{ }}` expressions is interpolated into a string.
+    ///
+    /// `NaN` and `Infinity` have no JSON representation, and evaluate to
+    /// `null`.
+    pub fn evaluate<E: Into<Expression>>(&self, expression: E) -> Result<Value> {
+        Ok(self.evaluate_value(&expression.into().0)?.into_json())
+    }
+
+    /// Evaluates an `if` condition. As on GitHub, the condition only holds
+    /// when the job succeeded so far, unless it calls one of the status
+    /// functions.
+    pub fn condition<E: Into<Expression>>(&self, expression: E) -> Result<bool> {
+        let expression = expression.into().0;
+        let status_check = match single_expression(&expression) {
+            Some(expression) => !Ast::parse(expression)?.calls_status_function(),
+            None if expression.contains("${{") => true,
+            None => !Ast::parse(&expression)?.calls_status_function(),
+        };
+        let value = self.evaluate_value(&expression)?;
+
+        Ok(value.is_truthy() && (!status_check || self.status == JobStatus::Success))
+    }
+
+    /// Replaces every `${{ }}` expression in `text` with its value.
+    pub fn interpolate(&self, text: &str) -> Result<String> {
+        let mut output = String::new();
+        for part in split_template(text)? {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Expression(expression) => {
+                    output.push_str(&self.eval(&Ast::parse(expression)?)?.to_string())
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn evaluate_value(&self, expression: &str) -> Result<Val> {
+        if let Some(expression) = single_expression(expression) {
+            self.eval(&Ast::parse(expression)?)
+        } else if expression.contains("${{") {
+            Ok(Val::String(self.interpolate(expression)?))
+        } else {
+            self.eval(&Ast::parse(expression)?)
+        }
+    }
+
+    fn eval(&self, ast: &Ast) -> Result<Val> {
+        Ok(match ast {
+            Ast::String(value) => Val::String(value.clone()),
+            Ast::Number(value) => Val::Number(*value),
+            Ast::Bool(value) => Val::Bool(*value),
+            Ast::Null => Val::Null,
+            Ast::Path(path) => {
+                let (name, path) = path.split_first().expect("paths are never empty");
+                let root = match &self.contexts {
+                    Value::Object(contexts) => contexts
+                        .get(name)
+                        .or_else(|| {
+                            contexts
+                                .iter()
+                                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                                .map(|(_, value)| value)
+                        })
+                        .map_or(Val::Null, |value| Val::from(value.clone())),
+                    _ => Val::Null,
+                };
+                path.iter()
+                    .fold((root, false), |(value, filtered), name| {
+                        value.select(name, filtered)
+                    })
+                    .0
+            }
+            Ast::Index(object, index) => {
+                let object = self.eval(object)?;
+                match self.eval(index)? {
+                    Val::Number(index) => match object {
+                        Val::Array(items) if index >= 0.0 && index.fract() == 0.0 => {
+                            items.into_iter().nth(index as usize).unwrap_or(Val::Null)
+                        }
+                        _ => Val::Null,
+                    },
+                    index => object.property(&index.to_string()),
+                }
+            }
+            Ast::Unary(operand) => Val::Bool(!self.eval(operand)?.is_truthy()),
+            Ast::Binary(operator, left, right) => {
+                let left = self.eval(left)?;
+                match operator.as_str() {
+                    "&&" if !left.is_truthy() => left,
+                    "||" if left.is_truthy() => left,
+                    "&&" | "||" => self.eval(right)?,
+                    operator => {
+                        let right = self.eval(right)?;
+                        Val::Bool(match operator {
+                            "==" => left.loose_eq(&right),
+                            "!=" => !left.loose_eq(&right),
+                            "<" => left.loose_cmp(&right) == Some(Ordering::Less),
+                            "<=" => matches!(
+                                left.loose_cmp(&right),
+                                Some(Ordering::Less | Ordering::Equal)
+                            ),
+                            ">" => left.loose_cmp(&right) == Some(Ordering::Greater),
+                            ">=" => matches!(
+                                left.loose_cmp(&right),
+                                Some(Ordering::Greater | Ordering::Equal)
+                            ),
+                            operator => {
+                                return Err(Error::InvalidExpression(format!(
+                                    "unknown operator `{operator}`"
+                                )))
+                            }
+                        })
+                    }
+                }
+            }
+            Ast::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                self.call(function, args)?
+            }
+        })
+    }
+
+    fn call(&self, function: &str, args: Vec<Val>) -> Result<Val> {
+        let arity = |min: usize, max: usize| {
+            if args.len() < min || args.len() > max {
+                Err(Error::InvalidExpression(format!(
+                    "`{function}` expects {min} to {max} arguments, got {}",
+                    args.len()
+                )))
+            } else {
+                Ok(())
+            }
+        };
+
+        Ok(match function.to_ascii_lowercase().as_str() {
+            "contains" => {
+                arity(2, 2)?;
+                Val::Bool(match &args[0] {
+                    Val::Array(items) => items.iter().any(|item| item.loose_eq(&args[1])),
+                    search => search
+                        .to_string()
+                        .to_lowercase()
+                        .contains(&args[1].to_string().to_lowercase()),
+                })
+            }
+            "startswith" => {
+                arity(2, 2)?;
+                let value = args[0].to_string().to_lowercase();
+                Val::Bool(value.starts_with(&args[1].to_string().to_lowercase()))
+            }
+            "endswith" => {
+                arity(2, 2)?;
+                let value = args[0].to_string().to_lowercase();
+                Val::Bool(value.ends_with(&args[1].to_string().to_lowercase()))
+            }
+            "format" => {
+                arity(1, usize::MAX)?;
+                Val::String(format(&args[0].to_string(), &args[1..])?)
+            }
+            "join" => {
+                arity(1, 2)?;
+                let separator = args.get(1).map_or(",".to_string(), Val::to_string);
+                match &args[0] {
+                    Val::Array(items) => Val::String(
+                        items
+                            .iter()
+                            .map(Val::to_string)
+                            .collect::<Vec<_>>()
+                            .join(&separator),
+                    ),
+                    value => Val::String(value.to_string()),
+                }
+            }
+            "tojson" => {
+                arity(1, 1)?;
+                let json = serde_json::to_string_pretty(&args[0].clone().into_json())
+                    .map_err(|error| Error::InvalidExpression(error.to_string()))?;
+                Val::String(json)
+            }
+            "fromjson" => {
+                arity(1, 1)?;
+                let value: Value = serde_json::from_str(&args[0].to_string()).map_err(|error| {
+                    Error::InvalidExpression(format!("`fromJSON` got invalid JSON: {error}"))
+                })?;
+                Val::from(value)
+            }
+            // No files are read: the hash is the one GitHub returns when no
+            // file matches.
+            "hashfiles" => {
+                arity(1, usize::MAX)?;
+                Val::String(String::new())
+            }
+            "success" => {
+                arity(0, 0)?;
+                Val::Bool(self.status == JobStatus::Success)
+            }
+            "failure" => {
+                arity(0, 0)?;
+                Val::Bool(self.status == JobStatus::Failure)
+            }
+            "cancelled" => {
+                arity(0, 0)?;
+                Val::Bool(self.status == JobStatus::Cancelled)
+            }
+            "always" => {
+                arity(0, 0)?;
+                Val::Bool(true)
+            }
+            _ => {
+                return Err(Error::InvalidExpression(format!(
+                    "unknown function `{function}`"
+                )))
+            }
+        })
+    }
+}
+
+/// Returns the content of `text` if it consists of a single `${{ }}`
+/// expression.
+fn single_expression(text: &str) -> Option<&str> {
+    match split_template(text.trim()).ok()?.as_slice() {
+        [Part::Expression(expression)] => Some(expression),
+        _ => None,
+    }
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Expression(&'a str),
+}
+
+/// Splits text into literal text and the contents of the `${{ }}`
+/// expressions in it. The `}}` of an expression can appear in its string
+/// literals.
+fn split_template(text: &str) -> Result<Vec<Part<'_>>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${{") {
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let expression = &rest[start + 3..];
+        let mut quoted = false;
+        let end = expression
+            .char_indices()
+            .find(|(i, c)| {
+                if *c == '\'' {
+                    quoted = !quoted;
+                }
+                !quoted && expression[*i..].starts_with("}}")
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| {
+                Error::InvalidExpression(format!("unterminated expression in `{text}`"))
+            })?;
+        parts.push(Part::Expression(&expression[..end]));
+        rest = &expression[end + 2..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+/// Replaces the `{0}`, `{1}`, ... placeholders of `template` with `args`.
+/// Braces are escaped by doubling them.
+fn format(template: &str, args: &[Val]) -> Result<String> {
+    let invalid = || Error::InvalidExpression(format!("invalid format string `{template}`"));
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '{' => {
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    index.push(c);
+                }
+                let arg = index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| args.get(index))
+                    .ok_or_else(invalid)?;
+                output.push_str(&arg.to_string());
+            }
+            '}' => return Err(invalid()),
+            c => output.push(c),
+        }
+    }
+    Ok(output)
+}
+
+/// A value during evaluation. Unlike JSON, numbers can be `NaN` or infinite.
+#[derive(Debug, Clone, PartialEq)]
+enum Val {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Val>),
+    Object(IndexMap<String, Val>),
+}
+
+impl Val {
+    /// `false`, `0`, `-0`, `NaN`, `''` and `null` are falsy, every other
+    /// value is truthy.
+    fn is_truthy(&self) -> bool {
+        match self {
+            Val::Null => false,
+            Val::Bool(value) => *value,
+            Val::Number(value) => *value != 0.0 && !value.is_nan(),
+            Val::String(value) => !value.is_empty(),
+            Val::Array(_) | Val::Object(_) => true,
+        }
+    }
+
+    fn to_number(&self) -> f64 {
+        match self {
+            Val::Null => 0.0,
+            Val::Bool(value) => f64::from(u8::from(*value)),
+            Val::Number(value) => *value,
+            Val::String(value) => parse_number(value.trim()).unwrap_or(f64::NAN),
+            Val::Array(_) | Val::Object(_) => f64::NAN,
+        }
+    }
+
+    /// Compares values the way GitHub does: strings ignoring case, arrays
+    /// and objects by content, and other values of different types by
+    /// converting them to numbers.
+    fn loose_eq(&self, other: &Val) -> bool {
+        match (self, other) {
+            (Val::String(a), Val::String(b)) => a.to_lowercase() == b.to_lowercase(),
+            (Val::Array(_), Val::Array(_)) | (Val::Object(_), Val::Object(_)) => self == other,
+            (Val::Array(_) | Val::Object(_), _) | (_, Val::Array(_) | Val::Object(_)) => false,
+            _ => self.to_number() == other.to_number(),
+        }
+    }
+
+    fn loose_cmp(&self, other: &Val) -> Option<Ordering> {
+        match (self, other) {
+            (Val::String(a), Val::String(b)) => Some(a.to_lowercase().cmp(&b.to_lowercase())),
+            (Val::Array(_) | Val::Object(_), _) | (_, Val::Array(_) | Val::Object(_)) => None,
+            _ => self.to_number().partial_cmp(&other.to_number()),
+        }
+    }
+
+    /// Reads a property, ignoring case when there is no exact match.
+    fn property(self, name: &str) -> Val {
+        let Val::Object(mut object) = self else {
+            return Val::Null;
+        };
+        if let Some(value) = object.swap_remove(name) {
+            return value;
+        }
+        object
+            .into_iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map_or(Val::Null, |(_, value)| value)
+    }
+
+    /// Applies a step of a property path. After a `*` filter, the following
+    /// properties are read from every item.
+    fn select(self, name: &str, filtered: bool) -> (Val, bool) {
+        match (name, self) {
+            ("*", Val::Array(items)) if filtered => (
+                Val::Array(
+                    items
+                        .into_iter()
+                        .flat_map(|item| match item {
+                            Val::Array(items) => items,
+                            Val::Object(object) => object.into_values().collect(),
+                            _ => Vec::new(),
+                        })
+                        .collect(),
+                ),
+                true,
+            ),
+            ("*", Val::Array(items)) => (Val::Array(items), true),
+            ("*", Val::Object(object)) => (Val::Array(object.into_values().collect()), true),
+            ("*", _) => (Val::Array(Vec::new()), true),
+            (name, Val::Array(items)) if filtered => (
+                Val::Array(
+                    items
+                        .into_iter()
+                        .map(|item| item.property(name))
+                        .filter(|value| *value != Val::Null)
+                        .collect(),
+                ),
+                true,
+            ),
+            (name, value) => (value.property(name), false),
+        }
+    }
+
+    fn into_json(self) -> Value {
+        match self {
+            Val::Null => Value::Null,
+            Val::Bool(value) => Value::Bool(value),
+            Val::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                Value::from(value as i64)
+            }
+            Val::Number(value) => {
+                serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number)
+            }
+            Val::String(value) => Value::String(value),
+            Val::Array(items) => Value::Array(items.into_iter().map(Val::into_json).collect()),
+            Val::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_json()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Val {
+    /// Converts the value to a string, as when it is interpolated.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Val::Null => Ok(()),
+            Val::Bool(value) => write!(f, "{value}"),
+            Val::Number(value) if value.is_nan() => write!(f, "NaN"),
+            Val::Number(value) if value.is_infinite() => {
+                write!(f, "{}Infinity", if *value < 0.0 { "-" } else { "" })
+            }
+            Val::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                write!(f, "{}", *value as i64)
+            }
+            Val::Number(value) => write!(f, "{value}"),
+            Val::String(value) => write!(f, "{value}"),
+            Val::Array(_) => write!(f, "Array"),
+            Val::Object(_) => write!(f, "Object"),
+        }
+    }
+}
+
+impl From<Value> for Val {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => Val::Null,
+            Value::Bool(value) => Val::Bool(value),
+            Value::Number(value) => Val::Number(value.as_f64().unwrap_or(f64::NAN)),
+            Value::String(value) => Val::String(value),
+            Value::Array(items) => Val::Array(items.into_iter().map(Val::from).collect()),
+            Value::Object(object) => Val::from(object),
+        }
+    }
+}
+
+impl From<serde_json::Map<String, Value>> for Val {
+    fn from(object: serde_json::Map<String, Value>) -> Self {
+        Val::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (key, Val::from(value)))
+                .collect(),
+        )
+    }
+}
+
+/// Parses a number the way GitHub converts strings to numbers: decimal,
+/// hexadecimal (`0x`) or exponent notation, `Infinity`, and `''` as `0`.
+fn parse_number(value: &str) -> Option<f64> {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+    };
+    if value.is_empty() {
+        Some(0.0)
+    } else if digits == "Infinity" {
+        Some(sign * f64::INFINITY)
+    } else if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| sign * value as f64)
+    } else if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+    {
+        digits.parse::<f64>().ok().map(|value| sign * value)
+    } else {
+        None
+    }
+}
+
+/// The syntax tree of an expression, following the grammar GitHub
+/// describes.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Ast {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+    /// A property path, such as `github.event.pull_request.title`. A `*`
+    /// step filters every item of an object or array.
+    Path(Vec<String>),
+    /// A property or item of a value, such as `steps['build'].outputs` or
+    /// `fromJSON(needs.setup.outputs.matrix).os`.
+    Index(Box<Ast>, Box<Ast>),
+    Call(String, Vec<Ast>),
+    Unary(Box<Ast>),
+    Binary(String, Box<Ast>, Box<Ast>),
+}
+
+const OPERATORS: [&[&str]; 4] = [&["||"], &["&&"], &["==", "!="], &["<=", ">=", "<", ">"]];
+
+impl Ast {
+    /// Parses an expression, without the surrounding `${{ }}`.
+    pub(crate) fn parse(input: &str) -> Result<Ast> {
+        let mut parser = Parser { input: input.chars().collect(), pos: 0 };
+        let ast = parser.binary(0)?;
+        parser.skip_whitespace();
+        if parser.pos < parser.input.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(ast)
+    }
+
+    fn calls_status_function(&self) -> bool {
+        match self {
+            Ast::Call(function, args) => {
+                matches!(
+                    function.to_ascii_lowercase().as_str(),
+                    "success" | "failure" | "cancelled" | "always"
+                ) || args.iter().any(Ast::calls_status_function)
+            }
+            Ast::Index(object, index) => {
+                object.calls_status_function() || index.calls_status_function()
+            }
+            Ast::Unary(operand) => operand.calls_status_function(),
+            Ast::Binary(_, left, right) => {
+                left.calls_status_function() || right.calls_status_function()
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Parser {
+    input: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> Error {
+        let input: String = self.input.iter().collect();
+        Error::InvalidExpression(format!("{message} at {} in `{input}`", self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.input.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let end = self.pos + token.len();
+        if end <= self.input.len() && self.input[self.pos..end].iter().copied().eq(token.chars()) {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{token}`")))
+        }
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Ast> {
+        if level == OPERATORS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(op) = OPERATORS[level].iter().find(|op| self.eat(op)) {
+            let right = self.binary(level + 1)?;
+            left = Ast::Binary(op.to_string(), Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Ast> {
+        if self.eat("!") {
+            Ok(Ast::Unary(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.error("expected an identifier"));
+        }
+        Ok(self.input[start..self.pos].iter().collect())
+    }
+
+    fn primary(&mut self) -> Result<Ast> {
+        if self.eat("(") {
+            let ast = self.binary(0)?;
+            self.expect(")")?;
+            return self.path(ast);
+        }
+        if self.eat("'") {
+            let mut value = String::new();
+            loop {
+                let Some(&c) = self.input.get(self.pos) else {
+                    return Err(self.error("unterminated string"));
+                };
+                self.pos += 1;
+                if c == '\'' {
+                    if self.input.get(self.pos) == Some(&'\'') {
+                        self.pos += 1;
+                    } else {
+                        return self.path(Ast::String(value));
+                    }
+                }
+                value.push(c);
+            }
+        }
+        self.skip_whitespace();
+        if self
+            .input
+            .get(self.pos)
+            .is_some_and(|c| c.is_ascii_digit() || *c == '-' || *c == '.')
+        {
+            let start = self.pos;
+            self.pos += 1;
+            while let Some(&c) = self.input.get(self.pos) {
+                let exponent_sign = matches!(c, '-' | '+')
+                    && matches!(self.input.get(self.pos - 1), Some('e' | 'E'))
+                    && !self.input[start..self.pos].contains(&'x');
+                if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            let number: String = self.input[start..self.pos].iter().collect();
+            return match parse_number(&number) {
+                Some(number) => Ok(Ast::Number(number)),
+                None => Err(self.error(&format!("invalid number `{number}`"))),
+            };
+        }
+
+        let identifier = self.identifier()?;
+        match identifier.as_str() {
+            "true" => Ok(Ast::Bool(true)),
+            "false" => Ok(Ast::Bool(false)),
+            "null" => Ok(Ast::Null),
+            "NaN" => Ok(Ast::Number(f64::NAN)),
+            "Infinity" => Ok(Ast::Number(f64::INFINITY)),
+            _ if self.eat("(") => {
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.binary(0)?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                self.path(Ast::Call(identifier, args))
+            }
+            _ => self.path(Ast::Path(vec![identifier])),
+        }
+    }
+
+    /// Parses the property and index accesses that follow a value.
+    fn path(&mut self, mut ast: Ast) -> Result<Ast> {
+        loop {
+            if self.input.get(self.pos) == Some(&'.') {
+                self.pos += 1;
+                let name = if self.eat("*") {
+                    "*".to_string()
+                } else {
+                    self.identifier()?
+                };
+                ast = match ast {
+                    Ast::Path(mut path) => {
+                        path.push(name);
+                        Ast::Path(path)
+                    }
+                    ast => Ast::Index(Box::new(ast), Box::new(Ast::String(name))),
+                };
+            } else if self.input.get(self.pos) == Some(&'[') {
+                self.pos += 1;
+                let index = if self.eat("*") {
+                    Ast::String("*".to_string())
+                } else {
+                    self.binary(0)?
+                };
+                self.expect("]")?;
+                ast = match (ast, index) {
+                    (Ast::Path(mut path), Ast::String(name)) => {
+                        path.push(name);
+                        Ast::Path(path)
+                    }
+                    (ast, index) => Ast::Index(Box::new(ast), Box::new(index)),
+                };
+            } else {
+                return Ok(ast);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::ctx::Context;
+
+    fn mock() -> MockContext {
+        MockContext::new(json!({
+            "github": {
+                "event_name": "push",
+                "ref": "refs/tags/v1.2.0",
+                "event": {
+                    "commits": [
+                        { "message": "Fix", "author": { "name": "a" } },
+                        { "message": "Docs", "author": { "name": "b" } }
+                    ]
+                }
+            },
+            "env": { "RETRIES": "0x10", "EMPTY": "" },
+            "matrix": { "os": "ubuntu-latest", "rust": 1.75 },
+            "needs": {
+                "build": { "result": "success", "outputs": { "matrix": "[\"a\", \"b\"]" } },
+                "lint": { "result": "skipped" }
+            }
+        }))
+    }
+
+    #[test]
+    fn job_conditions() {
+        let github = Context::github();
+        let tag = github.ref_().starts_with("refs/tags/".into());
+        let push = github.event_name().eq("push".into());
+        let release = push.and(tag);
+
+        assert!(mock().condition(release.clone()).unwrap());
+
+        let pull_request = MockContext::new(json!({
+            "github": { "event_name": "pull_request", "ref": "refs/pull/1/merge" }
+        }));
+        assert!(!pull_request.condition(release.clone()).unwrap());
+
+        let failed = mock().status(JobStatus::Failure);
+        assert!(!failed.condition(release.clone()).unwrap());
+        assert!(failed.condition(Context::failure()).unwrap());
+        assert!(failed
+            .condition(Context::always().and(release.clone()))
+            .unwrap());
+        assert!(!failed.condition(Context::cancelled()).unwrap());
+        assert!(failed.condition("always()").unwrap());
+        assert!(failed.condition("failure()").unwrap());
+        assert!(!failed.condition("github.event_name == 'push'").unwrap());
+    }
+
+    #[test]
+    fn loose_equality_and_truthiness() {
+        let mock = mock();
+        let cases = [
+            ("github.EVENT_NAME == 'PUSH'", json!(true)),
+            ("env.RETRIES == 16", json!(true)),
+            ("env.EMPTY == 0", json!(true)),
+            ("null == false", json!(true)),
+            ("'1e2' == 100", json!(true)),
+            ("'abc' == 0", json!(false)),
+            ("'abc' != 'abc'", json!(false)),
+            ("matrix.rust >= 1.7 && matrix.rust < '2'", json!(true)),
+            ("matrix.missing.deep", json!(null)),
+            ("env.EMPTY || 'fallback'", json!("fallback")),
+            ("matrix.os && matrix.rust", json!(1.75)),
+            ("!env.EMPTY", json!(true)),
+            ("!!matrix", json!(true)),
+            ("NaN == NaN", json!(false)),
+            ("github.event.commits.*.author.name", json!(["a", "b"])),
+            ("github.event.commits[1].message", json!("Docs")),
+            ("needs.*.result", json!(["success", "skipped"])),
+            ("needs['build'].result", json!("success")),
+        ];
+
+        for (expression, expected) in cases {
+            assert_eq!(mock.evaluate(expression).unwrap(), expected, "{expression}");
+        }
+    }
+
+    #[test]
+    fn built_in_functions() {
+        let mock = mock();
+        let cases = [
+            ("contains(github.ref, 'TAGS')", json!(true)),
+            ("contains(needs.*.result, 'failure')", json!(false)),
+            ("contains(fromJSON('[1, 2]'), '2')", json!(true)),
+            ("endsWith(github.ref, '.0')", json!(true)),
+            (
+                "format('{0}-{{{1}}}', matrix.os, matrix.rust)",
+                json!("ubuntu-latest-{1.75}"),
+            ),
+            (
+                "join(fromJSON(needs.build.outputs.matrix), ', ')",
+                json!("a, b"),
+            ),
+            ("join('abc')", json!("abc")),
+            ("fromJSON(needs.build.outputs.matrix)[0]", json!("a")),
+            (
+                "toJSON(matrix)",
+                json!("{\n  \"os\": \"ubuntu-latest\",\n  \"rust\": 1.75\n}"),
+            ),
+            ("hashFiles('**/Cargo.lock')", json!("")),
+            ("success() && !cancelled()", json!(true)),
+        ];
+
+        for (expression, expected) in cases {
+            assert_eq!(mock.evaluate(expression).unwrap(), expected, "{expression}");
+        }
+
+        assert!(mock.evaluate("unknown()").is_err());
+        assert!(mock.evaluate("startsWith('a')").is_err());
+        assert!(mock.evaluate("format('{1}', 'a')").is_err());
+        assert!(mock.evaluate("github.ref ==").is_err());
+    }
+
+    #[test]
+    fn interpolation() {
+        let mock = mock();
+        let key = Context::format("{0}-cargo", [Context::matrix().get("os")]);
+
+        assert_eq!(mock.evaluate(key).unwrap(), json!("ubuntu-latest-cargo"));
+        assert_eq!(
+            mock.interpolate("rust ${{ matrix.rust }} on ${{ format('{{{0}}}', matrix.os) }}")
+                .unwrap(),
+            "rust 1.75 on {ubuntu-latest}"
+        );
+        assert_eq!(mock.evaluate("v${{ 1 == 1 }}").unwrap(), json!("vtrue"));
+    }
+}
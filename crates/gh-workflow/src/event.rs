@@ -4,6 +4,7 @@ use derive_setters::Setters;
 use indexmap::IndexMap;
 use merge::Merge;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::is_default;
 
@@ -816,13 +817,13 @@ impl Watch {
 #[setters(strip_option, into)]
 pub struct WorkflowCall {
     /// Inputs for the workflow call
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub inputs: IndexMap<String, WorkflowCallInput>,
     /// Outputs from the workflow call
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub outputs: IndexMap<String, WorkflowCallOutput>,
     /// Secrets for the workflow call
-    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub secrets: IndexMap<String, WorkflowCallSecret>,
 }
 
@@ -831,6 +832,50 @@ impl WorkflowCall {
         self.inputs.insert(name.into(), input);
         self
     }
+
+    pub fn add_output(mut self, name: impl Into<String>, output: WorkflowCallOutput) -> Self {
+        self.outputs.insert(name.into(), output);
+        self
+    }
+
+    pub fn add_secret(mut self, name: impl Into<String>, secret: WorkflowCallSecret) -> Self {
+        self.secrets.insert(name.into(), secret);
+        self
+    }
+}
+
+/// Type of a workflow call input
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkflowCallInputType {
+    Boolean,
+    Number,
+    #[default]
+    String,
+}
+
+impl WorkflowCallInputType {
+    /// Checks whether a value passed through `with` has this type. Expressions
+    /// are only known at runtime and are accepted for every type.
+    pub fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::String(s)) if s.contains("${{") => true,
+            (WorkflowCallInputType::Boolean, Value::Bool(_)) => true,
+            (WorkflowCallInputType::Number, Value::Number(_)) => true,
+            (WorkflowCallInputType::String, Value::String(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for WorkflowCallInputType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkflowCallInputType::Boolean => write!(f, "boolean"),
+            WorkflowCallInputType::Number => write!(f, "number"),
+            WorkflowCallInputType::String => write!(f, "string"),
+        }
+    }
 }
 
 /// Configuration for workflow call input
@@ -838,17 +883,43 @@ impl WorkflowCall {
 #[setters(strip_option, into)]
 pub struct WorkflowCallInput {
     /// Description of the input
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
     /// Indicates if the input is required
-    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub required: bool,
     /// Type of the input
     #[serde(rename = "type")]
-    pub input_type: String,
+    pub input_type: WorkflowCallInputType,
     /// Default value for the input
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub default: Option<String>,
+    pub default: Option<Value>,
+}
+
+impl WorkflowCallInput {
+    /// Creates a `boolean` input.
+    pub fn boolean() -> Self {
+        Self {
+            input_type: WorkflowCallInputType::Boolean,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `number` input.
+    pub fn number() -> Self {
+        Self {
+            input_type: WorkflowCallInputType::Number,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a `string` input.
+    pub fn string() -> Self {
+        Self {
+            input_type: WorkflowCallInputType::String,
+            ..Default::default()
+        }
+    }
 }
 
 /// Configuration for workflow call output
@@ -856,22 +927,30 @@ pub struct WorkflowCallInput {
 #[setters(strip_option, into)]
 pub struct WorkflowCallOutput {
     /// Description of the output
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
     /// Value of the output
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub value: String,
 }
 
+impl WorkflowCallOutput {
+    /// Creates an output with the given value, typically the output of one of
+    /// the jobs of the workflow.
+    pub fn new<T: ToString>(value: T) -> Self {
+        Self { value: value.to_string(), ..Default::default() }
+    }
+}
+
 /// Configuration for workflow call secret
 #[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Setters, Eq)]
 #[setters(strip_option, into)]
 pub struct WorkflowCallSecret {
     /// Description of the secret
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
     /// Indicates if the secret is required
-    #[serde(skip_serializing_if = "is_default")]
+    #[serde(default, skip_serializing_if = "is_default")]
     pub required: bool,
 }
 
@@ -14,3 +14,17 @@ impl Expression {
         Self(expr.to_string())
     }
 }
+
+impl From<&str> for Expression {
+    /// Converts a string slice into an `Expression`.
+    fn from(value: &str) -> Self {
+        Expression::new(value)
+    }
+}
+
+impl From<String> for Expression {
+    /// Converts a string into an `Expression`.
+    fn from(value: String) -> Self {
+        Expression(value)
+    }
+}
@@ -1,80 +1,1174 @@
 //! This module provides functionality to customize generation of the GitHub
 //! Actions workflow files.
 
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 use derive_setters::Setters;
+use indexmap::IndexMap;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::error::{Error, Result};
-use crate::Workflow;
+use crate::pin::annotate;
+use crate::step::resolve_step_ids;
+use crate::{Action, ActionLock, ActionRuns, JobValue, Jobs, Workflow, YamlOptions};
+
+const COMMENT: &str = include_str!("./comment.yml");
+
+/// The first line of every generated file, whatever its header, so that
+/// generated files can be told apart from others.
+const GENERATED_MARKER: &str = "# @generated by gh-workflow\n";
+
+/// The file that a [`Generate`] writes.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Document {
+    Workflow(Workflow),
+    Action(Action),
+}
+
+/// A generated file, ready to be compared with the one on disk.
+struct Rendered {
+    kind: &'static str,
+    path: PathBuf,
+    content: String,
+}
+
+/// How [`Generate`] and [`Project`] treat the files on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Writes the files that are missing or out of date.
+    Write,
+    /// Fails if a file is missing or out of date, without writing anything.
+    /// The error for an outdated file carries a diff of the changes.
+    Check,
+    /// Prints the changes that [`Mode::Write`] would make, without writing
+    /// anything.
+    DryRun,
+}
+
+impl Mode {
+    /// Checks the files on CI, where the `CI` environment variable is set,
+    /// and writes them otherwise.
+    fn from_env() -> Self {
+        if std::env::var("CI").is_ok() {
+            Mode::Check
+        } else {
+            Mode::Write
+        }
+    }
+}
+
+/// Where generated files are read from and written to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Target {
+    /// The file system.
+    #[default]
+    FileSystem,
+    /// A set of files kept in memory, so that generation can be tested
+    /// without touching the file system.
+    Memory(MemoryFiles),
+}
+
+impl From<MemoryFiles> for Target {
+    fn from(value: MemoryFiles) -> Self {
+        Target::Memory(value)
+    }
+}
+
+impl Target {
+    fn read(&self, path: &Path) -> Option<String> {
+        match self {
+            Target::FileSystem => std::fs::read_to_string(path).ok(),
+            Target::Memory(files) => files.get(path),
+        }
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        match self {
+            Target::FileSystem => {
+                let Ok(entries) = std::fs::read_dir(dir) else {
+                    return Ok(Vec::new());
+                };
+                entries.map(|entry| Ok(entry?.path())).collect()
+            }
+            Target::Memory(files) => Ok(files
+                .paths()
+                .into_iter()
+                .filter(|path| path.parent() == Some(dir))
+                .collect()),
+        }
+    }
+
+    /// Writes all files together. On the file system every file is first
+    /// written next to its destination, and then moved in place.
+    fn write(&self, files: &[&Rendered]) -> Result<()> {
+        if let Target::Memory(memory) = self {
+            for file in files {
+                memory.insert(file.path.clone(), file.content.clone());
+            }
+            return Ok(());
+        }
+
+        let mut staged = Vec::new();
+        for file in files {
+            std::fs::create_dir_all(file.path.parent().ok_or(Error::IO(
+                std::io::Error::other("Invalid parent dir(s) path"),
+            ))?)?;
+            let mut tmp = file.path.clone().into_os_string();
+            tmp.push(".tmp");
+            let tmp = PathBuf::from(tmp);
+            if let Err(e) = std::fs::write(&tmp, &file.content) {
+                for tmp in staged {
+                    let _ = std::fs::remove_file(tmp);
+                }
+                return Err(e.into());
+            }
+            staged.push(tmp);
+        }
+
+        for (file, tmp) in files.iter().zip(staged) {
+            std::fs::rename(tmp, &file.path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Files kept in memory by [`Target::Memory`]. Clones share the same files.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFiles(Arc<Mutex<BTreeMap<PathBuf, String>>>);
+
+impl MemoryFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the content of a file.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<String> {
+        self.files().get(path.as_ref()).cloned()
+    }
+
+    /// Adds or replaces a file.
+    pub fn insert<P: Into<PathBuf>, C: ToString>(&self, path: P, content: C) {
+        self.files().insert(path.into(), content.to_string());
+    }
+
+    /// Lists the paths of all files, in order.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files().keys().cloned().collect()
+    }
+
+    fn files(&self) -> MutexGuard<'_, BTreeMap<PathBuf, String>> {
+        // Every update is a single insert, so the files stay consistent even
+        // if another thread panicked while holding the lock.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl PartialEq for MemoryFiles {
+    /// Two `MemoryFiles` are equal when they share the same files.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MemoryFiles {}
+
+/// The comment written at the top of every generated file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Header {
+    /// A warning not to edit the file by hand.
+    #[default]
+    Default,
+    /// A custom text. Every line is written as a YAML comment.
+    Custom(String),
+    /// A custom text in which `{source}` is replaced with the path of the
+    /// source file that created the header, and `{version}` with the version
+    /// of this crate. Create it with [`Header::template`].
+    Template { text: String, source: String },
+    /// No header, only the line that marks the file as generated.
+    None,
+}
+
+impl Header {
+    /// Creates a custom header.
+    pub fn custom<T: ToString>(text: T) -> Self {
+        Header::Custom(text.to_string())
+    }
+
+    /// Creates a header from a template, recording the source file of the
+    /// caller as `{source}`.
+    #[track_caller]
+    pub fn template<T: ToString>(text: T) -> Self {
+        let source = std::panic::Location::caller().file().replace('\\', "/");
+        Header::Template { text: text.to_string(), source }
+    }
+
+    /// Renders the text that precedes the content of a generated file.
+    fn render(&self) -> String {
+        fn comment(text: &str) -> String {
+            let lines: String = text
+                .lines()
+                .map(|line| match line {
+                    "" => "#\n".to_string(),
+                    line => format!("# {line}\n"),
+                })
+                .collect();
+            format!("{lines}\n")
+        }
+
+        match self {
+            Header::Default => format!("{COMMENT}\n"),
+            Header::Custom(text) => comment(text),
+            Header::Template { text, source } => comment(
+                &text
+                    .replace("{source}", source)
+                    .replace("{version}", env!("CARGO_PKG_VERSION")),
+            ),
+            Header::None => String::new(),
+        }
+    }
+}
+
+/// Removes the comments and blank lines at the top of a file.
+fn strip_header(content: &str) -> &str {
+    let header: usize = content
+        .split_inclusive('\n')
+        .take_while(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('#')
+        })
+        .map(str::len)
+        .sum();
+    &content[header..]
+}
+
+/// Where and how generated files are written.
+#[derive(Setters, Clone, Default, PartialEq, Eq)]
+#[setters(
+    strip_option,
+    into,
+    generate_delegates(ty = "Generate", field = "output"),
+    generate_delegates(ty = "Project", field = "shared_output")
+)]
+pub struct Output {
+    /// The directory that generated paths are relative to. Defaults to the
+    /// root of the git repository, or to no directory at all for an
+    /// in-memory target.
+    pub root: Option<PathBuf>,
+    /// The directory of the workflows, relative to the root. Defaults to
+    /// `.github/workflows`.
+    pub workflows_dir: Option<PathBuf>,
+    /// Defaults to [`Mode::Check`] on CI and [`Mode::Write`] otherwise.
+    pub mode: Option<Mode>,
+    pub target: Target,
+    pub header: Header,
+    /// Whether [`Mode::Check`] ignores differences in the header, and only
+    /// compares the content that follows it.
+    pub ignore_header: bool,
+    /// How the YAML is formatted. Defaults to the plain output of
+    /// `serde_yaml`.
+    pub yaml: Option<YamlOptions>,
+    /// The [`ActionLock`] file, relative to the root, such as
+    /// `.github/actions.lock`. When set, every action is pinned to the
+    /// commit SHA in the lock file, and an action that isn't in it fails
+    /// the generation.
+    pub lock_file: Option<PathBuf>,
+}
+
+impl Output {
+    fn resolve_root_dir(&self) -> Result<PathBuf> {
+        match (&self.root, &self.target) {
+            (Some(root), _) => Ok(root.clone()),
+            (None, Target::Memory(_)) => Ok(PathBuf::new()),
+            (None, Target::FileSystem) => {
+                let root_dir = String::from_utf8(
+                    Command::new("git")
+                        .args(["rev-parse", "--show-toplevel"])
+                        .output()?
+                        .stdout,
+                )?;
+                Ok(PathBuf::from(root_dir.trim()))
+            }
+        }
+    }
+
+    fn resolve_workflows_dir(&self) -> PathBuf {
+        self.workflows_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(".github").join("workflows"))
+    }
+
+    fn resolve_mode(&self) -> Mode {
+        self.mode.unwrap_or_else(Mode::from_env)
+    }
+
+    fn resolve_lock(&self) -> Result<Option<ActionLock>> {
+        let Some(lock_file) = &self.lock_file else {
+            return Ok(None);
+        };
+        let path = self.resolve_root_dir()?.join(lock_file);
+        match self.target.read(&path) {
+            Some(content) => Ok(Some(ActionLock::parse(&content)?)),
+            None => Err(Error::MissingLockFile(path)),
+        }
+    }
+}
 
 #[derive(Setters, Clone)]
 #[setters(strip_option, into)]
 pub struct Generate {
-    workflow: Workflow,
+    #[setters(skip)]
+    document: Document,
     name: String,
+    /// The path of the generated file, relative to the root. Defaults to
+    /// `<workflows dir>/<name>` for workflows and `action.yml` for actions.
+    path: Option<PathBuf>,
+    #[setters(skip)]
+    output: Output,
 }
 
 impl Generate {
     pub fn new(workflow: Workflow) -> Self {
-        Self { workflow, name: "ci.yml".to_string() }
+        let workflow = organize_job_dependency(workflow);
+        Self {
+            document: Document::Workflow(workflow),
+            name: "ci.yml".to_string(),
+            path: None,
+            output: Output::default(),
+        }
     }
 
-    fn check_file(&self, path: &PathBuf, content: &str) -> Result<()> {
-        if let Ok(prev) = std::fs::read_to_string(path) {
-            if content != prev {
-                Err(Error::OutdatedWorkflow)
-            } else {
-                Ok(())
+    /// Creates a `Generate` that writes the metadata file of an action.
+    pub fn action(action: Action) -> Self {
+        Self {
+            document: Document::Action(action),
+            name: "action.yml".to_string(),
+            path: None,
+            output: Output::default(),
+        }
+    }
+
+    pub fn generate(&self) -> Result<()> {
+        let rendered = self.render(&self.output)?;
+        sync(&self.output, vec![rendered])
+    }
+
+    /// Returns the workflow to generate, or `None` for an action.
+    pub(crate) fn workflow(&self) -> Option<&Workflow> {
+        match &self.document {
+            Document::Workflow(workflow) => Some(workflow),
+            Document::Action(_) => None,
+        }
+    }
+
+    /// Returns the path of the generated file, relative to the root, as
+    /// `uses` refers to it, such as `.github/workflows/release.yml`.
+    pub(crate) fn uses_path(&self) -> String {
+        let path = self.relative_path(&self.output);
+        let components: Vec<_> = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        components.join("/")
+    }
+
+    fn relative_path(&self, output: &Output) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| match self.document {
+            Document::Workflow(_) => output.resolve_workflows_dir().join(self.name.as_str()),
+            Document::Action(_) => PathBuf::from(self.name.as_str()),
+        })
+    }
+
+    /// Validates the document and renders the file, without touching the
+    /// file on disk.
+    fn render(&self, output: &Output) -> Result<Rendered> {
+        let mut document = self.document.clone();
+        match &mut document {
+            Document::Workflow(workflow) => resolve_job_placeholders(workflow)?,
+            Document::Action(action) => resolve_step_placeholders(action)?,
+        }
+        let pins = match output.resolve_lock()? {
+            Some(lock) => match &mut document {
+                Document::Workflow(workflow) => lock.pin_workflow(workflow)?,
+                Document::Action(action) => lock.pin_action(action)?,
+            },
+            None => Vec::new(),
+        };
+
+        let (kind, diagnostics, yaml) = match &document {
+            Document::Workflow(workflow) => {
+                let yaml = match &output.yaml {
+                    Some(options) => workflow.to_string_with(options)?,
+                    None => workflow.to_string()?,
+                };
+                ("Workflow", workflow.validate(), yaml)
             }
-        } else {
-            Err(Error::MissingWorkflowFile(path.clone()))
+            Document::Action(action) => {
+                let yaml = match &output.yaml {
+                    Some(options) => action.to_string_with(options)?,
+                    None => action.to_string()?,
+                };
+                ("Action", action.validate(), yaml)
+            }
+        };
+        if !diagnostics.is_empty() {
+            return Err(Error::InvalidWorkflow(diagnostics));
         }
+
+        let path = output.resolve_root_dir()?.join(self.relative_path(output));
+
+        Ok(Rendered {
+            kind,
+            path,
+            content: format!(
+                "{GENERATED_MARKER}{}{}",
+                output.header.render(),
+                annotate(&yaml, &pins)
+            ),
+        })
+    }
+}
+
+/// Generates a set of workflows and actions together. Every file is rendered
+/// and validated before any of them is written, so an invalid workflow
+/// leaves all files untouched. The output settings of the project apply to
+/// all of its files, which can't have output settings of their own.
+#[derive(Clone, Default)]
+pub struct Project {
+    files: Vec<Generate>,
+    /// The output settings of every file.
+    shared_output: Output,
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to generate. Its name and path are kept, but its output
+    /// settings must be left unset: generating a project with a file that
+    /// sets them, such as its own header or mode, fails with
+    /// [`Error::ConflictingOutput`] instead of ignoring them.
+    pub fn add_file(mut self, generate: Generate) -> Self {
+        self.files.push(generate);
+        self
+    }
+
+    /// Adds a workflow, generated as `<workflows dir>/<name>`.
+    pub fn add_workflow<N: ToString>(self, name: N, workflow: Workflow) -> Self {
+        self.add_file(Generate::new(workflow).name(name.to_string()))
+    }
+
+    /// Adds a composite action, generated at the given path relative to the
+    /// root.
+    pub fn add_action<P: Into<PathBuf>>(self, path: P, action: Action) -> Self {
+        self.add_file(Generate::action(action).path(path.into()))
     }
 
     pub fn generate(&self) -> Result<()> {
-        let comment = include_str!("./comment.yml");
+        let default = Output::default();
+        if let Some(file) = self.files.iter().find(|file| file.output != default) {
+            let path = file.relative_path(&self.shared_output);
+            return Err(Error::ConflictingOutput(path));
+        }
+
+        let rendered = self
+            .files
+            .iter()
+            .map(|file| file.render(&self.shared_output))
+            .collect::<Result<Vec<_>>>()?;
+
+        let stale = stale_files(&self.shared_output, &rendered)?;
+        for path in stale.iter() {
+            println!("Stale workflow file: {}", path.display());
+        }
+
+        sync(&self.shared_output, rendered)?;
+
+        if !stale.is_empty() && self.shared_output.resolve_mode() == Mode::Check {
+            return Err(Error::StaleWorkflowFiles(stale));
+        }
+        Ok(())
+    }
+}
 
-        let root_dir = String::from_utf8(
-            Command::new("git")
-                .args(["rev-parse", "--show-toplevel"])
-                .output()?
-                .stdout,
-        )?;
+/// The state of a generated file on disk.
+enum Status {
+    UpToDate,
+    Outdated(String),
+    Missing,
+}
 
-        let path = PathBuf::from(root_dir.trim())
-            .join(".github")
-            .join("workflows")
-            .join(self.name.as_str());
+fn check_file(target: &Target, path: &Path, content: &str, ignore_header: bool) -> Status {
+    match target.read(path) {
+        Some(prev) if prev == content => Status::UpToDate,
+        Some(prev) if ignore_header && strip_header(&prev) == strip_header(content) => {
+            Status::UpToDate
+        }
+        Some(prev) => Status::Outdated(prev),
+        None => Status::Missing,
+    }
+}
 
-        let content = format!("{}\n{}", comment, self.workflow.to_string()?);
+/// Renders a unified diff from the file on disk to the generated one.
+fn diff(path: &Path, prev: &str, content: &str) -> String {
+    let path = path.display().to_string();
+    similar::TextDiff::from_lines(prev, content)
+        .unified_diff()
+        .header(&path, &path)
+        .to_string()
+}
 
-        let result = self.check_file(&path, &content);
+/// Finds the files in the workflows directory that were generated but are no
+/// longer generated.
+fn stale_files(output: &Output, rendered: &[Rendered]) -> Result<Vec<PathBuf>> {
+    let dir = output
+        .resolve_root_dir()?
+        .join(output.resolve_workflows_dir());
+    let mut stale: Vec<_> = output
+        .target
+        .list(&dir)?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "yml" || ext == "yaml")
+        })
+        .filter(|path| rendered.iter().all(|file| file.path != *path))
+        .filter(|path| {
+            output
+                .target
+                .read(path)
+                .is_some_and(|content| is_generated(&content))
+        })
+        .collect();
+    stale.sort();
+    Ok(stale)
+}
 
-        if std::env::var("CI").is_ok() {
-            result
-        } else {
-            match result {
-                Ok(()) => {
-                    println!("Workflow file is up-to-date: {}", path.display());
-                    Ok(())
-                }
-                Err(Error::OutdatedWorkflow) => {
-                    std::fs::write(path.clone(), content)?;
-                    println!("Updated workflow file: {}", path.display());
-                    Ok(())
-                }
-                Err(Error::MissingWorkflowFile(path)) => {
-                    std::fs::create_dir_all(path.parent().ok_or(Error::IO(
-                        std::io::Error::other("Invalid parent dir(s) path"),
-                    ))?)?;
-                    std::fs::write(path.clone(), content)?;
-                    println!("Generated workflow file: {}", path.display());
-                    Ok(())
+/// Whether a file was generated, either with the marker on its first line or
+/// with the default header that files were generated with before the marker.
+fn is_generated(content: &str) -> bool {
+    content.starts_with(GENERATED_MARKER) || content.starts_with(COMMENT)
+}
+
+/// Brings the files of the target in line with the rendered ones, according
+/// to the mode.
+fn sync(output: &Output, rendered: Vec<Rendered>) -> Result<()> {
+    let mode = output.resolve_mode();
+    let mut error = None;
+    let mut pending = Vec::new();
+    for file in rendered.iter() {
+        let ignore_header = output.ignore_header && mode == Mode::Check;
+        match (
+            check_file(&output.target, &file.path, &file.content, ignore_header),
+            mode,
+        ) {
+            (Status::UpToDate, Mode::Check) => {}
+            (Status::UpToDate, _) => {
+                println!("{} file is up-to-date: {}", file.kind, file.path.display())
+            }
+            (Status::Outdated(prev), Mode::Check) => {
+                let diff = diff(&file.path, &prev, &file.content);
+                eprintln!("{diff}");
+                error.get_or_insert(Error::OutdatedWorkflow { path: file.path.clone(), diff });
+            }
+            (Status::Missing, Mode::Check) => {
+                error.get_or_insert(Error::MissingWorkflowFile(file.path.clone()));
+            }
+            (Status::Outdated(prev), Mode::DryRun) => {
+                println!(
+                    "Would update {} file: {}",
+                    file.kind.to_lowercase(),
+                    file.path.display()
+                );
+                println!("{}", diff(&file.path, &prev, &file.content));
+            }
+            (Status::Missing, Mode::DryRun) => {
+                println!(
+                    "Would generate {} file: {}",
+                    file.kind.to_lowercase(),
+                    file.path.display()
+                );
+            }
+            (Status::Outdated(_), Mode::Write) => pending.push((file, "Updated")),
+            (Status::Missing, Mode::Write) => pending.push((file, "Generated")),
+        }
+    }
+
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    let files: Vec<_> = pending.iter().map(|(file, _)| *file).collect();
+    output.target.write(&files)?;
+    for (file, action) in pending {
+        println!(
+            "{action} {} file: {}",
+            file.kind.to_lowercase(),
+            file.path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the jobs referenced through [`crate::Need::Job`] to job ids,
+/// adding the ones that aren't part of the workflow yet, and orders the jobs
+/// so that every job comes after the jobs it needs.
+fn organize_job_dependency(mut workflow: Workflow) -> Workflow {
+    let Some(Jobs(mut jobs)) = workflow.jobs.take() else {
+        return workflow;
+    };
+
+    let mut job_id = 0;
+    let mut index = 0;
+    while index < jobs.len() {
+        let dependencies = std::mem::take(&mut jobs[index].dependencies);
+        let mut needs = Vec::new();
+
+        for dependency in dependencies {
+            let existing = jobs
+                .iter()
+                .find(|(_, job)| job.key == dependency.key)
+                .map(|(id, _)| id.clone());
+
+            let id = match existing {
+                Some(id) => id,
+                None => {
+                    while jobs.contains_key(&format!("job-{job_id}")) {
+                        job_id += 1;
+                    }
+                    let id = format!("job-{job_id}");
+                    jobs.insert(id.clone(), dependency);
+                    id
                 }
-                Err(e) => Err(e),
+            };
+
+            needs.push(id);
+        }
+
+        let job = &mut jobs[index];
+        for id in needs {
+            let job_needs = job.needs.get_or_insert_with(Vec::new);
+            if !job_needs.contains(&id) {
+                job_needs.push(id);
+            }
+        }
+
+        index += 1;
+    }
+
+    workflow.jobs = Some(Jobs(sort_jobs(jobs)));
+    workflow
+}
+
+/// Replaces the placeholders that stand in for job ids in expressions, such
+/// as the ones created by [`crate::Job::need_output`], with the ids of the
+/// jobs in the workflow, and numbers the unnamed steps of every job that
+/// [`crate::Step::output`] gave a placeholder id. Only references inside
+/// `${{ }}` are replaced, so text that merely looks like a placeholder is
+/// kept as is.
+fn resolve_job_placeholders(workflow: &mut Workflow) -> Result<()> {
+    let Some(Jobs(jobs)) = workflow.jobs.as_mut() else {
+        return Ok(());
+    };
+    let ids: Vec<_> = jobs
+        .iter()
+        .map(|(id, job)| {
+            (
+                format!("needs.{}", job.key.placeholder()),
+                format!("needs.{id}"),
+            )
+        })
+        .collect();
+
+    for job in jobs.values_mut() {
+        let mut ids = ids.clone();
+        ids.extend(resolve_step_ids(
+            job.steps.as_deref_mut().unwrap_or_default(),
+        ));
+        let Some(resolved) = resolve_references(&*job, &ids)? else {
+            continue;
+        };
+        *job = JobValue {
+            key: job.key,
+            dependencies: std::mem::take(&mut job.dependencies),
+            interface: job.interface.take(),
+            allowed_lints: std::mem::take(&mut job.allowed_lints),
+            ..resolved
+        };
+    }
+    Ok(())
+}
+
+/// Numbers the unnamed steps of a composite action that [`crate::Step::output`]
+/// gave a placeholder id, and replaces the references to them.
+fn resolve_step_placeholders(action: &mut Action) -> Result<()> {
+    let ActionRuns::Composite { steps } = &mut action.runs;
+    let ids = resolve_step_ids(steps);
+    if let Some(resolved) = resolve_references(&*action, &ids)? {
+        *action = resolved;
+    }
+    Ok(())
+}
+
+/// Replaces the references in the expressions of `value`, and returns the
+/// result if any of them changed.
+fn resolve_references<T: Serialize + DeserializeOwned>(
+    value: &T,
+    ids: &[(String, String)],
+) -> Result<Option<T>> {
+    if ids.is_empty() {
+        return Ok(None);
+    }
+    let mut value = serde_yaml::to_value(value)?;
+    if !resolve_value(&mut value, ids) {
+        return Ok(None);
+    }
+    Ok(Some(serde_yaml::from_value(value)?))
+}
+
+/// Resolves the placeholders in the strings of `value`, and returns whether
+/// any of them changed.
+fn resolve_value(value: &mut serde_yaml::Value, ids: &[(String, String)]) -> bool {
+    match value {
+        serde_yaml::Value::String(text) => {
+            let resolved = resolve_expressions(text, ids);
+            let changed = resolved != *text;
+            *text = resolved;
+            changed
+        }
+        serde_yaml::Value::Sequence(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= resolve_value(value, ids);
+            }
+            changed
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            let mut changed = false;
+            for value in mapping.values_mut() {
+                changed |= resolve_value(value, ids);
             }
+            changed
+        }
+        serde_yaml::Value::Tagged(tagged) => resolve_value(&mut tagged.value, ids),
+        _ => false,
+    }
+}
+
+fn resolve_expressions(text: &str, ids: &[(String, String)]) -> String {
+    let mut resolved = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        let end = start + length + 2;
+        let mut expression = rest[start..end].to_string();
+        for (placeholder, id) in ids {
+            expression = expression.replace(placeholder.as_str(), id);
+        }
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(&expression);
+        rest = &rest[end..];
+    }
+    resolved.push_str(rest);
+    resolved
+}
+
+/// Orders jobs so that each job follows the jobs it needs, keeping the
+/// original order otherwise. Jobs that are part of a cycle keep their
+/// relative order.
+fn sort_jobs(mut jobs: IndexMap<String, JobValue>) -> IndexMap<String, JobValue> {
+    fn visit(
+        id: &str,
+        jobs: &IndexMap<String, JobValue>,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) {
+        if order.iter().any(|o| o == id) || visiting.iter().any(|v| v == id) {
+            return;
         }
+        let Some(job) = jobs.get(id) else {
+            return;
+        };
+
+        visiting.push(id.to_string());
+        for need in job.needs.iter().flatten() {
+            visit(need, jobs, visiting, order);
+        }
+        visiting.pop();
+        order.push(id.to_string());
+    }
+
+    let mut order = Vec::new();
+    for id in jobs.keys() {
+        visit(id, &jobs, &mut Vec::new(), &mut order);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|id| jobs.shift_remove_entry(&id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_snapshot;
+
+    use super::*;
+    use crate::ctx::Context;
+    use crate::{Job, Step, StepValue};
+
+    #[test]
+    fn add_needs_job() {
+        let base_job: Job = Job::new("Base job");
+
+        let job1: Job =
+            Job::new("The first job that has dependency for base_job").add_need(base_job.clone());
+        let job2: Job =
+            Job::new("The second job that has dependency for base_job").add_need(base_job.clone());
+
+        let workflow = Workflow::new("All jobs were added to workflow")
+            .add_job("base_job", base_job)
+            .add_job("with-dependency-1", job1)
+            .add_job("with-dependency-2", job2);
+
+        let workflow = organize_job_dependency(workflow);
+
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+
+    #[test]
+    fn matches_needs_by_job() {
+        let first: Job = Job::new("Same job");
+        let second: Job = Job::new("Same job");
+        let dependent: Job = Job::new("Dependent").add_need(second);
+
+        let workflow = Workflow::new("Identical jobs")
+            .add_job("first", first)
+            .add_job("dependent", dependent);
+        let workflow = organize_job_dependency(workflow);
+
+        let jobs = workflow.jobs.unwrap().0;
+        assert_eq!(jobs["dependent"].needs, Some(vec!["job-0".to_string()]));
+        assert_eq!(jobs["job-0"], jobs["first"]);
+    }
+
+    #[test]
+    fn compares_jobs_as_written() {
+        let base: Job = Job::new("Base");
+        let job: Job = Job::new("Build")
+            .add_need(base)
+            .allow_lint(crate::LintRule::ScriptInjection)
+            .add_step(Step::checkout());
+        let workflow = Workflow::new("CI").add_job("build", job);
+
+        let parsed = Workflow::parse(&workflow.to_string().unwrap()).unwrap();
+
+        assert_eq!(
+            parsed.jobs.unwrap().0["build"],
+            workflow.jobs.unwrap().0["build"]
+        );
+    }
+
+    #[test]
+    fn missing_add_job() {
+        let base_job: Job = Job::new("Base job");
+
+        let job1: Job =
+            Job::new("The first job that has dependency for base_job").add_need(base_job.clone());
+        let job2: Job =
+            Job::new("The second job that has dependency for base_job").add_need(base_job.clone());
+
+        let workflow = Workflow::new("base_job was not added to workflow jobs")
+            .add_job("with-dependency-1", job1)
+            .add_job("with-dependency-2", job2);
+
+        let workflow = organize_job_dependency(workflow);
+
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+
+    #[test]
+    fn job_outputs() {
+        let mut version = Step::new("Get version").run("echo version=1.0.0 >> $GITHUB_OUTPUT");
+        let version_output = version.output("version");
+
+        let mut build: Job = Job::new("Build").add_step(version);
+        let version = build.add_output("version", version_output);
+
+        let mut release: Job = Job::new("Release");
+        let release_version = release.need_output(&version);
+        let release = release.add_step(Step::new("Tag").run(format!("git tag v{release_version}")));
+
+        let workflow = Workflow::new("Job outputs")
+            .add_job("release", release.clone())
+            .add_job("build", build);
+
+        let mut workflow = organize_job_dependency(workflow);
+        resolve_job_placeholders(&mut workflow).unwrap();
+
+        assert_snapshot!(workflow.to_string().unwrap());
+    }
+
+    #[test]
+    fn keeps_placeholder_text_outside_expressions() {
+        let mut build: Job = Job::new("Build");
+        let version = build.add_output("version", Context::github().sha());
+
+        let mut release: Job = Job::new("Release");
+        let release_version = release.need_output(&version);
+        let release = release.add_step(Step::new("Tag").run(format!("git tag v{release_version}")));
+
+        let workflow = Workflow::new("Job outputs")
+            .add_job("build", build)
+            .add_job("release", release);
+        let mut workflow = organize_job_dependency(workflow);
+
+        // A script that prints the placeholder of the build job, which is
+        // only an id inside an expression.
+        let jobs = &mut workflow.jobs.as_mut().unwrap().0;
+        let script = format!("echo '{}'", jobs["build"].key.placeholder());
+        jobs["release"]
+            .steps
+            .as_mut()
+            .unwrap()
+            .push(StepValue::run(script.clone()));
+
+        resolve_job_placeholders(&mut workflow).unwrap();
+
+        let steps = workflow.jobs.unwrap().0["release"].steps.clone().unwrap();
+        assert_eq!(
+            steps[0].run.as_deref(),
+            Some("git tag v${{ needs.build.outputs.version }}")
+        );
+        assert_eq!(steps[1].run.as_deref(), Some(script.as_str()));
+    }
+
+    #[test]
+    fn numbers_unnamed_steps() {
+        fn workflow() -> Workflow {
+            let mut toolchain = Step::toolchain().add_stable();
+            let cache_key = toolchain.output("cachekey");
+            let job: Job = Job::new("Build")
+                .add_step(Step::checkout())
+                .add_step(toolchain)
+                .add_step(Step::new("Print").run(format!("echo {cache_key}")));
+            Workflow::new("CI").add_job("build", job)
+        }
+
+        let output = Output::default().target(MemoryFiles::new());
+        let first = Generate::new(workflow()).render(&output).unwrap();
+        let second = Generate::new(workflow()).render(&output).unwrap();
+
+        assert_eq!(first.content, second.content);
+        assert!(first.content.contains("- id: setup-rust-toolchain-2\n"));
+        assert!(first
+            .content
+            .contains("run: echo ${{ steps.setup-rust-toolchain-2.outputs.cachekey }}\n"));
+    }
+
+    #[test]
+    fn sorts_jobs_by_needs() {
+        let deploy: Job = Job::new("Deploy").add_need("test");
+        let test: Job = Job::new("Test").add_need("build");
+        let build: Job = Job::new("Build");
+
+        let workflow = Workflow::new("Sorted")
+            .add_job("deploy", deploy)
+            .add_job("test", test)
+            .add_job("build", build);
+
+        let workflow = organize_job_dependency(workflow);
+        let ids: Vec<_> = workflow.jobs.unwrap().0.into_keys().collect();
+
+        assert_eq!(ids, vec!["build", "test", "deploy"]);
+    }
+
+    #[test]
+    fn reports_stale_files() {
+        let files = MemoryFiles::new();
+        files.insert(".github/workflows/ci.yml", COMMENT);
+        files.insert(".github/workflows/old.yml", COMMENT);
+        files.insert(".github/workflows/manual.yml", "name: Manual\n");
+        files.insert("action.yml", COMMENT);
+
+        // Files generated with another header, or without one.
+        for (name, header) in [
+            ("custom.yml", Header::custom("Old header")),
+            ("plain.yml", Header::None),
+        ] {
+            Generate::new(Workflow::new(name))
+                .name(name)
+                .header(header)
+                .target(files.clone())
+                .mode(Mode::Write)
+                .generate()
+                .unwrap();
+        }
+
+        for header in [Header::Default, Header::None] {
+            let output = Output::default().target(files.clone()).header(header);
+            let rendered = Generate::new(Workflow::new("CI")).render(&output).unwrap();
+            let stale = stale_files(&output, &[rendered]).unwrap();
+
+            assert_eq!(
+                stale,
+                vec![
+                    PathBuf::from(".github/workflows/custom.yml"),
+                    PathBuf::from(".github/workflows/old.yml"),
+                    PathBuf::from(".github/workflows/plain.yml"),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn generate_modes() {
+        let files = MemoryFiles::new();
+        let path = ".github/workflows/ci.yml";
+        files.insert(path, format!("{COMMENT}\nname: Old\n"));
+        let generate = Generate::new(Workflow::new("CI")).target(files.clone());
+
+        let check = generate.clone().mode(Mode::Check).generate();
+        generate.clone().mode(Mode::DryRun).generate().unwrap();
+        let after_dry_run = files.get(path).unwrap();
+        generate.clone().mode(Mode::Write).generate().unwrap();
+
+        let Err(Error::OutdatedWorkflow { diff, .. }) = check else {
+            panic!("expected an outdated workflow, got {check:?}");
+        };
+        assert!(diff.contains("-name: Old\n+name: CI\n"), "{diff}");
+        assert_eq!(after_dry_run, format!("{COMMENT}\nname: Old\n"));
+        assert_eq!(
+            files.get(path).unwrap(),
+            format!("{GENERATED_MARKER}{COMMENT}\nname: CI\n")
+        );
+        generate.mode(Mode::Check).generate().unwrap();
+    }
+
+    #[test]
+    fn generate_to_root() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Generate>();
+        assert_send_sync::<Project>();
+
+        let files = MemoryFiles::new();
+        Project::new()
+            .root("repo")
+            .workflows_dir("workflows")
+            .target(files.clone())
+            .mode(Mode::Write)
+            .add_workflow("ci.yml", Workflow::new("CI"))
+            .add_action("actions/setup/action.yml", Action::new("Setup"))
+            .generate()
+            .unwrap();
+
+        assert_eq!(
+            files.paths(),
+            vec![
+                PathBuf::from("repo/actions/setup/action.yml"),
+                PathBuf::from("repo/workflows/ci.yml"),
+            ]
+        );
+        assert_eq!(
+            files.get("repo/workflows/ci.yml").unwrap(),
+            format!("{GENERATED_MARKER}{COMMENT}\nname: CI\n")
+        );
+        assert!(files
+            .get("repo/actions/setup/action.yml")
+            .unwrap()
+            .ends_with("name: Setup\nruns:\n  using: composite\n  steps: []\n"));
+    }
+
+    #[test]
+    fn rejects_file_output_in_project() {
+        let files = MemoryFiles::new();
+        let project = Project::new()
+            .target(files.clone())
+            .mode(Mode::Write)
+            .add_workflow("ci.yml", Workflow::new("CI"))
+            .add_file(
+                Generate::new(Workflow::new("Release"))
+                    .name("release.yml")
+                    .header(Header::None),
+            );
+
+        let result = project.generate();
+
+        assert!(matches!(
+            result,
+            Err(Error::ConflictingOutput(path)) if path == Path::new(".github/workflows/release.yml")
+        ));
+        assert_eq!(files.paths(), Vec::<PathBuf>::new());
+    }
+
+    #[test]
+    fn custom_headers() {
+        let files = MemoryFiles::new();
+        let generate = Generate::new(Workflow::new("CI"))
+            .target(files.clone())
+            .mode(Mode::Write);
+
+        generate.clone().header(Header::None).generate().unwrap();
+        assert_eq!(
+            files.get(".github/workflows/ci.yml").unwrap(),
+            format!("{GENERATED_MARKER}name: CI\n")
+        );
+
+        generate
+            .clone()
+            .header(Header::custom("Generated file.\n\nDo not edit."))
+            .generate()
+            .unwrap();
+        assert_eq!(
+            files.get(".github/workflows/ci.yml").unwrap(),
+            format!("{GENERATED_MARKER}# Generated file.\n#\n# Do not edit.\n\nname: CI\n")
+        );
+
+        let header = Header::template("Generated by {source} with gh-workflow {version}.");
+        assert_eq!(
+            header.render(),
+            format!(
+                "# Generated by crates/gh-workflow/src/generate.rs with gh-workflow {}.\n\n",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+
+        let check = generate.mode(Mode::Check).header(header);
+        assert!(check.clone().generate().is_err());
+        check.ignore_header(true).generate().unwrap();
+    }
+
+    #[test]
+    fn pins_actions_from_lock_file() {
+        let files = MemoryFiles::new();
+        let workflow =
+            Workflow::new("CI").add_job("build", Job::new("Build").add_step(Step::checkout()));
+        let generate = Generate::new(workflow)
+            .target(files.clone())
+            .mode(Mode::Write)
+            .header(Header::None)
+            .lock_file(".github/actions.lock");
+
+        assert!(matches!(
+            generate.generate(),
+            Err(Error::MissingLockFile(_))
+        ));
+
+        files.insert(
+            ".github/actions.lock",
+            "actions/checkout@v5: 08c6903cd8c0fde910a37f88322edcfb5dd907a8\n",
+        );
+        generate.generate().unwrap();
+        assert!(files
+            .get(".github/workflows/ci.yml")
+            .unwrap()
+            .contains("uses: actions/checkout@08c6903cd8c0fde910a37f88322edcfb5dd907a8 # v5\n"));
     }
 }
@@ -2,6 +2,7 @@
 //! Job-related structures and implementations for GitHub workflow jobs.
 
 use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use derive_setters::Setters;
@@ -11,27 +12,14 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::concurrency::Concurrency;
+use crate::ctx::Context;
+use crate::generate::Generate;
 use crate::step::{Step, StepType, StepValue};
 use crate::{
-    private, Artifacts, Container, Defaults, Env, Expression, Input, Permissions, RetryStrategy,
-    Strategy,
+    private, Artifacts, Container, Defaults, Env, Expression, Input, JobEnvironment, LintRule,
+    Permissions, RetryStrategy, RunsOn, Strategy, WorkflowCall,
 };
 
-/// Represents the environment in which a job runs.
-#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
-#[serde(transparent)]
-pub struct RunsOn(Value);
-
-impl<T> From<T> for RunsOn
-where
-    T: Into<Value>,
-{
-    /// Converts a value into a `RunsOn` instance.
-    fn from(value: T) -> Self {
-        RunsOn(value.into())
-    }
-}
-
 /// Represents input parameters for a step.
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(transparent)]
@@ -66,6 +54,71 @@ impl Secrets {
     }
 }
 
+/// Represents a job that another job depends on, either by its id or by the
+/// job itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Need {
+    /// The id of a job that is part of the workflow.
+    Id(String),
+
+    /// A job whose id is resolved when the workflow is generated. The job is
+    /// added to the workflow if it isn't part of it already.
+    Job(Box<JobValue>),
+}
+
+impl From<&str> for Need {
+    fn from(value: &str) -> Self {
+        Need::Id(value.to_string())
+    }
+}
+
+impl From<String> for Need {
+    fn from(value: String) -> Self {
+        Need::Id(value)
+    }
+}
+
+impl<T: JobType> From<Job<T>> for Need {
+    fn from(value: Job<T>) -> Self {
+        Need::Job(Box::new(T::to_value(value)))
+    }
+}
+
+impl<T: JobType + Clone> From<&Job<T>> for Need {
+    fn from(value: &Job<T>) -> Self {
+        value.clone().into()
+    }
+}
+
+/// Identifies a job across its clones, so that a job referenced through
+/// [`Need::Job`] can be matched with the same job in the workflow. Every
+/// default value is unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct JobKey(u64);
+
+impl JobKey {
+    /// Stands in for the id of the job in expressions, until the id is
+    /// resolved when the workflow is generated.
+    pub(crate) fn placeholder(&self) -> String {
+        format!("<job:{}>", self.0)
+    }
+}
+
+/// Represents an output of a job, that other jobs can read through the
+/// `needs` context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobOutput {
+    job: JobValue,
+    name: String,
+}
+
+impl Default for JobKey {
+    fn default() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        JobKey(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 pub trait JobType: Any + Default + private::Sealed {
     fn to_value(j: Job<Self>) -> JobValue;
 }
@@ -98,6 +151,9 @@ pub struct UsesJob {
     pub with: Option<Input>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub secrets: Option<Secrets>,
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) interface: Option<WorkflowCall>,
 }
 
 impl private::Sealed for RunJob {}
@@ -112,8 +168,8 @@ impl JobType for RunJob {
 
 impl JobType for UsesJob {
     fn to_value(j: Job<Self>) -> JobValue {
-        let UsesJob { uses, with, secrets } = j.config;
-        JobValue { uses: Some(uses), with, secrets, ..j.value }
+        let UsesJob { uses, with, secrets, interface } = j.config;
+        JobValue { uses: Some(uses), with, secrets, interface, ..j.value }
     }
 }
 
@@ -124,7 +180,7 @@ pub struct Job<J: JobType = RunJob> {
     value: JobValue,
 }
 
-#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "kebab-case")]
 #[setters(
     strip_option,
@@ -142,6 +198,8 @@ pub struct JobValue {
     pub runs_on: Option<RunsOn>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub permissions: Option<Permissions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<JobEnvironment>,
     #[serde(skip_serializing_if = "Option::is_none", rename = "env")]
     #[setters(skip)]
     pub envs: Option<Env>,
@@ -177,8 +235,79 @@ pub struct JobValue {
     pub retry: Option<RetryStrategy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artifacts: Option<Artifacts>,
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) key: JobKey,
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) dependencies: Vec<JobValue>,
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) interface: Option<WorkflowCall>,
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) allowed_lints: Vec<LintRule>,
 }
 
+impl PartialEq for JobValue {
+    /// Compares the jobs as they are written to the workflow, leaving out the
+    /// settings that are only used while generating it, such as the jobs it
+    /// depends on.
+    fn eq(&self, other: &Self) -> bool {
+        let JobValue {
+            needs,
+            cond,
+            name,
+            runs_on,
+            permissions,
+            environment,
+            envs,
+            strategy,
+            steps,
+            uses,
+            container,
+            outputs,
+            concurrency,
+            timeout_minutes,
+            services,
+            secrets,
+            with,
+            defaults,
+            continue_on_error,
+            retry,
+            artifacts,
+            key: _,
+            dependencies: _,
+            interface: _,
+            allowed_lints: _,
+        } = self;
+
+        *needs == other.needs
+            && *cond == other.cond
+            && *name == other.name
+            && *runs_on == other.runs_on
+            && *permissions == other.permissions
+            && *environment == other.environment
+            && *envs == other.envs
+            && *strategy == other.strategy
+            && *steps == other.steps
+            && *uses == other.uses
+            && *container == other.container
+            && *outputs == other.outputs
+            && *concurrency == other.concurrency
+            && *timeout_minutes == other.timeout_minutes
+            && *services == other.services
+            && *secrets == other.secrets
+            && *with == other.with
+            && *defaults == other.defaults
+            && *continue_on_error == other.continue_on_error
+            && *retry == other.retry
+            && *artifacts == other.artifacts
+    }
+}
+
+impl Eq for JobValue {}
+
 impl Job {
     /// Creates a new `Job` that uses a reusable workflow.
     pub fn uses<Owner: ToString, Repo: ToString, Path: ToString, Version: ToString>(
@@ -202,6 +331,61 @@ impl Job {
             value: self.value,
         }
     }
+
+    /// Creates a new `Job` that calls a reusable workflow with the given
+    /// `workflow_call` interface. The inputs and secrets passed to the
+    /// workflow are checked against the interface when the workflow is
+    /// generated.
+    pub fn call<U: ToString>(self, uses: U, interface: WorkflowCall) -> Job<UsesJob> {
+        Job {
+            config: UsesJob {
+                uses: uses.to_string(),
+                interface: Some(interface),
+                ..Default::default()
+            },
+            value: self.value,
+        }
+    }
+
+    /// Creates a new `Job` that uses a reusable workflow from the same
+    /// repository, such as `.github/workflows/release.yml`.
+    pub fn uses_local<P: ToString>(self, path: P) -> Job<UsesJob> {
+        let path = path.to_string();
+        let path = path.trim_start_matches("./");
+        Job {
+            config: UsesJob { uses: format!("./{path}"), ..Default::default() },
+            value: self.value,
+        }
+    }
+
+    /// Creates a new `Job` that calls the workflow that `generate` writes,
+    /// using the path it is generated at. The inputs and secrets passed to
+    /// the workflow are checked against its `workflow_call` trigger when the
+    /// workflow is generated.
+    pub fn uses_workflow(self, generate: &Generate) -> Job<UsesJob> {
+        let interface = generate
+            .workflow()
+            .and_then(|workflow| workflow.on.as_ref())
+            .and_then(|on| on.workflow_call.clone())
+            .unwrap_or_default();
+        self.call(format!("./{}", generate.uses_path()), interface)
+    }
+}
+
+impl Job<UsesJob> {
+    /// Passes an input to the reusable workflow.
+    pub fn add_with<K: ToString, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        let with = self.config.with.take().unwrap_or_default();
+        self.config.with = Some(with.add(key, value));
+        self
+    }
+
+    /// Passes a secret to the reusable workflow.
+    pub fn add_secret<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        let secrets = self.config.secrets.take().unwrap_or_default();
+        self.config.secrets = Some(secrets.add(key, value));
+        self
+    }
 }
 
 impl<J: JobType> Job<J> {
@@ -210,7 +394,7 @@ impl<J: JobType> Job<J> {
         Self {
             value: JobValue {
                 name: Some(name.to_string()),
-                runs_on: Some(RunsOn(Value::from("ubuntu-latest"))),
+                runs_on: Some(RunsOn::default()),
                 ..Default::default()
             },
 
@@ -226,17 +410,102 @@ impl<J: JobType> Job<J> {
         }
     }
 
-    pub fn add_need<T: ToString>(mut self, job_id: T) -> Self {
-        if let Some(needs) = self.value.needs.as_mut() {
-            needs.push(job_id.to_string());
-        } else {
-            self.value.needs = Some(vec![job_id.to_string()]);
+    /// Adds a job that needs to complete before this job runs. The job can be
+    /// referenced by its id or passed directly, in which case its id is
+    /// resolved when the workflow is generated.
+    pub fn add_need<N: Into<Need>>(mut self, need: N) -> Self {
+        match need.into() {
+            Need::Id(job_id) => {
+                if let Some(needs) = self.value.needs.as_mut() {
+                    needs.push(job_id);
+                } else {
+                    self.value.needs = Some(vec![job_id]);
+                }
+            }
+            Need::Job(job) => self.value.dependencies.push(*job),
+        }
+        self
+    }
+
+    /// Reads `output` of another job, which renders as
+    /// `needs.<job>.outputs.<name>`, and makes this job depend on that job.
+    /// The job id is resolved when the workflow is generated.
+    pub fn need_output(&mut self, output: &JobOutput) -> Context<String> {
+        if !self
+            .value
+            .dependencies
+            .iter()
+            .any(|job| job.key == output.job.key)
+        {
+            self.value.dependencies.push(output.job.clone());
         }
+
+        Context::needs()
+            .get(output.job.key.placeholder())
+            .outputs()
+            .get(output.name.clone())
+    }
+
+    /// Suppresses a lint rule for this job.
+    pub fn allow_lint(mut self, rule: LintRule) -> Self {
+        self.value.allowed_lints.push(rule);
         self
     }
 }
 
+impl<J: JobType + Clone> Job<J> {
+    /// Adds an output to the job, and returns a handle that other jobs can
+    /// read it through with [`Job::need_output`]. The handle refers to the
+    /// job itself, so changes made to the job afterwards are kept as long as
+    /// the job is added to the workflow.
+    pub fn add_output(&mut self, name: impl Into<String>, value: Context<String>) -> JobOutput {
+        let name = name.into();
+        self.value
+            .outputs
+            .get_or_insert_with(Default::default)
+            .insert(name.clone(), value.to_string());
+
+        JobOutput { job: J::to_value(self.clone()), name }
+    }
+}
+
 impl Job<RunJob> {
+    /// Returns the `GITHUB_TOKEN` permissions that the steps of the job
+    /// declare with [`Step::require_permissions`], combined, or `None` if any
+    /// step doesn't declare them, since what it needs is unknown.
+    pub fn required_permissions(&self) -> Option<Permissions> {
+        self.config
+            .steps
+            .iter()
+            .flatten()
+            .try_fold(Permissions::default(), |union, step| {
+                Some(union.union(step.permissions.as_ref()?))
+            })
+    }
+
+    /// Sets the permissions of the job to the ones its steps need, keeping
+    /// the permissions already set on the job. Call it after adding the
+    /// steps. If any step doesn't declare its permissions, a job without
+    /// permissions is left unchanged so that it keeps the default ones of
+    /// the workflow, and a job with permissions only gets the ones of the
+    /// steps that declare them.
+    pub fn infer_permissions(mut self) -> Self {
+        let declared = self
+            .config
+            .steps
+            .iter()
+            .flatten()
+            .filter_map(|step| step.permissions.as_ref())
+            .fold(Permissions::default(), |union, permissions| {
+                union.union(permissions)
+            });
+        self.value.permissions = match self.value.permissions.take() {
+            Some(permissions) => Some(permissions.union(&declared)),
+            None => self.required_permissions(),
+        };
+        self
+    }
+
     /// Adds a step to the job.
     pub fn add_step<S: Into<Step<T>>, T: StepType>(mut self, step: S) -> Self {
         let mut steps = self.config.steps.take().unwrap_or_default();
@@ -1,5 +1,6 @@
 extern crate proc_macro;
 
+mod action;
 mod artifacts;
 mod cargo;
 mod concurrency;
@@ -8,14 +9,21 @@ mod defaults;
 mod env;
 mod environment;
 pub mod error;
+mod eval;
 mod event;
 mod expression;
 mod job;
+mod lint;
 mod permissions;
+mod pin;
+mod runner;
 mod secret;
 mod step;
 mod strategy;
+mod validate;
+mod yaml;
 
+pub mod actions;
 pub mod ctx;
 pub mod generate;
 pub mod release_plz;
@@ -23,6 +31,7 @@ mod rust_flag;
 pub mod toolchain;
 pub(crate) mod workflow;
 
+pub use action::*;
 pub use artifacts::*;
 pub use cargo::*;
 pub use concurrency::*;
@@ -30,15 +39,21 @@ pub use container::*;
 pub use defaults::*;
 pub use env::*;
 pub use environment::*;
+pub use eval::*;
 pub use event::*;
 pub use expression::*;
 pub use job::*;
+pub use lint::*;
 pub use permissions::*;
+pub use pin::*;
+pub use runner::*;
 pub use rust_flag::*;
 pub use secret::*;
 pub use step::*;
 pub use strategy::*;
+pub use validate::*;
 pub use workflow::*;
+pub use yaml::*;
 
 pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
     *value == T::default()
@@ -0,0 +1,430 @@
+//!
+//! Linting of workflows for common security problems.
+//! See: <https://docs.github.com/en/actions/security-for-github-actions/security-guides/security-hardening-for-github-actions>
+
+use std::fmt::{Display, Formatter};
+
+use serde_json::Value;
+
+use crate::permissions::SCOPES;
+use crate::pin::is_sha;
+use crate::{JobValue, Level, Permissions, StepValue, Workflow};
+
+/// Represents a lint rule. Rules can be suppressed for a whole workflow with
+/// [`Workflow::allow_lint`], or for a single job with
+/// [`Job::allow_lint`](crate::Job::allow_lint).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintRule {
+    /// An attacker-controlled field of `github.event`, such as the title of a
+    /// pull request, is interpolated directly into a script. Pass it through
+    /// an environment variable instead.
+    ScriptInjection,
+
+    /// A `pull_request_target` workflow checks out the head of the pull
+    /// request, running untrusted code with access to secrets.
+    UntrustedCheckout,
+
+    /// The workflow doesn't declare top-level `permissions`, so it gets the
+    /// default permissions of the repository.
+    MissingPermissions,
+
+    /// The workflow or a job grants write access to every scope, such as
+    /// with `write-all`.
+    BroadPermissions,
+
+    /// The workflow grants write access to a scope at the top level, which
+    /// applies to every job. Grant it to the jobs that need it instead.
+    TopLevelWritePermissions,
+
+    /// A third-party action or reusable workflow isn't pinned to a full
+    /// commit SHA.
+    UnpinnedAction,
+}
+
+impl LintRule {
+    /// Returns the id of the rule.
+    pub fn id(&self) -> &'static str {
+        match self {
+            LintRule::ScriptInjection => "script-injection",
+            LintRule::UntrustedCheckout => "untrusted-checkout",
+            LintRule::MissingPermissions => "missing-permissions",
+            LintRule::BroadPermissions => "broad-permissions",
+            LintRule::TopLevelWritePermissions => "top-level-write-permissions",
+            LintRule::UnpinnedAction => "unpinned-action",
+        }
+    }
+}
+
+impl Display for LintRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+/// Represents a problem found while linting a workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// The rule that found the problem.
+    pub rule: LintRule,
+
+    /// The id of the job, or `None` for the workflow itself.
+    pub job: Option<String>,
+
+    /// The index of the step in the job.
+    pub step: Option<usize>,
+
+    /// Describes the problem.
+    pub message: String,
+}
+
+impl Finding {
+    /// Returns the path of the problem in the workflow, such as
+    /// `jobs.build.steps[1]`.
+    pub fn path(&self) -> String {
+        match (&self.job, self.step) {
+            (None, _) => "workflow".to_string(),
+            (Some(job), None) => format!("jobs.{job}"),
+            (Some(job), Some(step)) => format!("jobs.{job}.steps[{step}]"),
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} [{}]", self.path(), self.message, self.rule)
+    }
+}
+
+/// The fields of `github.event` that anyone who can open an issue or a pull
+/// request controls.
+const UNTRUSTED_FIELDS: &[&str] = &[
+    ".title",
+    ".body",
+    ".message",
+    ".author.name",
+    ".author.email",
+    ".committer.name",
+    ".committer.email",
+    ".head.ref",
+    ".head.label",
+    ".head_branch",
+    ".default_branch",
+    ".page_name",
+];
+
+impl Workflow {
+    /// Suppresses a lint rule for the whole workflow.
+    pub fn allow_lint(mut self, rule: LintRule) -> Self {
+        self.allowed_lints.push(rule);
+        self
+    }
+
+    /// Checks the workflow for common security problems.
+    ///
+    /// Returns an empty list when no problems were found.
+    pub fn lint(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let jobs: Vec<_> = self.jobs.iter().flat_map(|jobs| jobs.0.iter()).collect();
+
+        match &self.permissions {
+            None if !jobs.iter().all(|(_, job)| job.permissions.is_some()) => {
+                findings.push(Finding {
+                    rule: LintRule::MissingPermissions,
+                    job: None,
+                    step: None,
+                    message: "the workflow doesn't declare `permissions`".to_string(),
+                });
+            }
+            Some(permissions) if grants_write_all(permissions) => findings.push(Finding {
+                rule: LintRule::BroadPermissions,
+                job: None,
+                step: None,
+                message: "the workflow grants write access to every scope".to_string(),
+            }),
+            Some(permissions) => {
+                let scopes: Vec<_> = SCOPES
+                    .iter()
+                    .filter(|scope| permissions.level(scope) == Some(Level::Write))
+                    .map(|scope| format!("`{scope}`"))
+                    .collect();
+                if !scopes.is_empty() {
+                    findings.push(Finding {
+                        rule: LintRule::TopLevelWritePermissions,
+                        job: None,
+                        step: None,
+                        message: format!(
+                            "the workflow grants write access to {} to every job; grant it to the jobs that need it",
+                            scopes.join(", ")
+                        ),
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        let pull_request_target = self
+            .on
+            .as_ref()
+            .is_some_and(|on| on.pull_request_target.is_some());
+
+        for (id, job) in jobs {
+            let mut job_findings = Vec::new();
+            lint_job(id, job, pull_request_target, &mut job_findings);
+            findings.extend(
+                job_findings
+                    .into_iter()
+                    .filter(|finding| !job.allowed_lints.contains(&finding.rule)),
+            );
+        }
+
+        findings.retain(|finding| !self.allowed_lints.contains(&finding.rule));
+        findings
+    }
+}
+
+fn lint_job(id: &str, job: &JobValue, pull_request_target: bool, findings: &mut Vec<Finding>) {
+    let finding = |rule, step, message| Finding { rule, job: Some(id.to_string()), step, message };
+
+    if let Some(permissions) = &job.permissions {
+        if grants_write_all(permissions) {
+            findings.push(finding(
+                LintRule::BroadPermissions,
+                None,
+                "the job grants write access to every scope".to_string(),
+            ));
+        }
+    }
+
+    if let Some(uses) = job.uses.as_deref().filter(|uses| is_unpinned(uses)) {
+        findings.push(finding(
+            LintRule::UnpinnedAction,
+            None,
+            format!("reusable workflow `{uses}` isn't pinned to a commit SHA"),
+        ));
+    }
+
+    for (index, step) in job.steps.iter().flatten().enumerate() {
+        for field in untrusted_fields(step) {
+            findings.push(finding(
+                LintRule::ScriptInjection,
+                Some(index),
+                format!("`{field}` is interpolated into a script; pass it through `env` instead"),
+            ));
+        }
+
+        if pull_request_target && checks_out_head(step) {
+            findings.push(finding(
+                LintRule::UntrustedCheckout,
+                Some(index),
+                "the step checks out the head of the pull request in a `pull_request_target` workflow".to_string(),
+            ));
+        }
+
+        if let Some(uses) = step.uses.as_deref().filter(|uses| is_unpinned(uses)) {
+            findings.push(finding(
+                LintRule::UnpinnedAction,
+                Some(index),
+                format!("action `{uses}` isn't pinned to a commit SHA"),
+            ));
+        }
+    }
+}
+
+fn grants_write_all(permissions: &Permissions) -> bool {
+    SCOPES
+        .iter()
+        .all(|scope| permissions.level(scope) == Some(Level::Write))
+}
+
+/// Finds the attacker-controlled fields used in the expressions of a script,
+/// either a `run` command or the `script` of `actions/github-script`.
+fn untrusted_fields(step: &StepValue) -> Vec<String> {
+    let script = step.with.as_ref().and_then(|with| with.0.get("script"));
+    let scripts = step
+        .run
+        .as_deref()
+        .into_iter()
+        .chain(script.and_then(Value::as_str));
+
+    let mut fields = Vec::new();
+    for script in scripts {
+        for expression in expressions(script) {
+            for field in context_paths(expression) {
+                let untrusted = field == "github.head_ref"
+                    || (field.starts_with("github.event.")
+                        && UNTRUSTED_FIELDS
+                            .iter()
+                            .any(|suffix| field.ends_with(suffix)));
+                if untrusted && !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Returns the contents of the `${{ }}` expressions of a string.
+fn expressions(string: &str) -> Vec<&str> {
+    let mut expressions = Vec::new();
+    let mut rest = string;
+    while let Some(start) = rest.find("${{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        expressions.push(&rest[start + 3..start + end]);
+        rest = &rest[start + end + 2..];
+    }
+    expressions
+}
+
+/// Returns the property paths of an expression, such as
+/// `github.event.issue.title`.
+fn context_paths(expression: &str) -> Vec<String> {
+    expression
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '*')))
+        .filter(|path| path.contains('.'))
+        .map(|path| path.trim_end_matches('.').to_string())
+        .collect()
+}
+
+fn checks_out_head(step: &StepValue) -> bool {
+    let is_checkout = step
+        .uses
+        .as_deref()
+        .is_some_and(|uses| uses.starts_with("actions/checkout@"));
+    let ref_ = step
+        .with
+        .as_ref()
+        .and_then(|with| with.0.get("ref"))
+        .and_then(Value::as_str);
+
+    is_checkout
+        && ref_.is_some_and(|ref_| {
+            ref_.contains("github.event.pull_request.head")
+                || ref_.contains("github.head_ref")
+                || ref_.contains("refs/pull/")
+        })
+}
+
+/// Checks whether a `uses` refers to a third-party action or workflow that
+/// isn't pinned to a commit SHA. Actions of the `actions` and `github`
+/// organizations, local actions and Docker images are trusted.
+fn is_unpinned(uses: &str) -> bool {
+    if uses.starts_with("./") || uses.starts_with("docker://") {
+        return false;
+    }
+    let owner = uses.split('/').next().unwrap_or_default();
+    if owner == "actions" || owner == "github" {
+        return false;
+    }
+    !uses.split_once('@').is_some_and(|(_, ref_)| is_sha(ref_))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Event, Job, PullRequestTarget, RunJob, Step};
+
+    type Rule = (LintRule, Option<String>, Option<usize>);
+
+    fn finding(rule: LintRule, job: Option<&str>, step: Option<usize>) -> Rule {
+        (rule, job.map(String::from), step)
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<Rule> {
+        findings
+            .iter()
+            .map(|finding| (finding.rule, finding.job.clone(), finding.step))
+            .collect()
+    }
+
+    #[test]
+    fn security_findings() {
+        let workflow =
+            Workflow::new("Triage")
+                .add_event(Event::default().pull_request_target(PullRequestTarget::default()))
+                .add_job(
+                    "triage",
+                    Job::new("Triage")
+                        .add_step(
+                            Step::checkout()
+                                .add_with(("ref", "${{ github.event.pull_request.head.sha }}")),
+                        )
+                        .add_step(Step::new("Greet").run(
+                            "echo \"${{ github.event.pull_request.title }}\" ${{ github.sha }}",
+                        ))
+                        .add_step(Step::new("Label").uses(
+                            "actions-ecosystem",
+                            "action-add-labels",
+                            "v1",
+                        ))
+                        .add_step(Step::new("Safe").uses(
+                            "actions-ecosystem",
+                            "action-add-labels",
+                            "18f1af5e3544586314bbe15c0273249c770b2daf",
+                        )),
+                )
+                .add_job(
+                    "publish",
+                    Job::<RunJob>::new("Publish").permissions(Permissions::write_all()),
+                );
+
+        let findings = workflow.lint();
+        assert_eq!(
+            rules(&findings),
+            vec![
+                finding(LintRule::MissingPermissions, None, None),
+                finding(LintRule::UntrustedCheckout, Some("triage"), Some(0)),
+                finding(LintRule::ScriptInjection, Some("triage"), Some(1)),
+                finding(LintRule::UnpinnedAction, Some("triage"), Some(2)),
+                finding(LintRule::BroadPermissions, Some("publish"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn suppressed_rules() {
+        let job = Job::new("Comment")
+            .add_step(Step::new("Comment").run("echo '${{ github.event.comment.body }}'"))
+            .add_step(
+                Step::new("Script")
+                    .uses("actions", "github-script", "v7")
+                    .add_with(("script", "console.log('${{ github.event.issue.title }}')")),
+            );
+
+        let workflow = Workflow::new("Comment")
+            .permissions(Permissions::default().issues(Level::Write))
+            .add_job("comment", job.clone());
+        let findings = workflow.lint();
+        assert_eq!(
+            findings
+                .iter()
+                .find(|finding| finding.job.is_none())
+                .unwrap()
+                .to_string(),
+            "workflow: the workflow grants write access to `issues` to every job; grant it to the jobs that need it [top-level-write-permissions]"
+        );
+        assert_eq!(
+            rules(&findings),
+            vec![
+                finding(LintRule::TopLevelWritePermissions, None, None),
+                finding(LintRule::ScriptInjection, Some("comment"), Some(0)),
+                finding(LintRule::ScriptInjection, Some("comment"), Some(1)),
+            ]
+        );
+
+        let workflow = workflow
+            .allow_lint(LintRule::TopLevelWritePermissions)
+            .add_job("comment", job.allow_lint(LintRule::ScriptInjection));
+        assert_eq!(workflow.lint(), vec![]);
+
+        let workflow = Workflow::new("Comment").permissions(Permissions::write_all());
+        assert_eq!(
+            rules(&workflow.lint()),
+            vec![finding(LintRule::BroadPermissions, None, None)]
+        );
+    }
+}
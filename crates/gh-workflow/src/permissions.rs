@@ -1,60 +1,339 @@
 //! Permission types for GitHub Workflow tokens.
 
+use std::fmt::Formatter;
+
 use derive_setters::Setters;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Workflow;
 
 /// Represents permissions for the `GITHUB_TOKEN`.
-#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
+///
+/// Serializes to the `read-all` or `write-all` shorthand when only [`all`]
+/// is set, and to a mapping of scopes otherwise. An empty mapping, `{}`,
+/// disables every permission.
+///
+/// [`all`]: Permissions::all
+#[derive(Debug, Setters, Clone, Default, PartialEq, Eq)]
 #[setters(strip_option, into)]
 pub struct Permissions {
+    /// The level of every scope that isn't set explicitly.
+    pub all: Option<Level>,
+
     /// Permissions for actions.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub actions: Option<Level>,
 
     /// Permissions for repository contents.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub contents: Option<Level>,
 
     /// Permissions for issues.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub issues: Option<Level>,
 
     /// Permissions for pull requests.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub pull_requests: Option<Level>,
 
     /// Permissions for deployments.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub deployments: Option<Level>,
 
     /// Permissions for checks.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub checks: Option<Level>,
 
     /// Permissions for statuses.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub statuses: Option<Level>,
 
     /// Permissions for packages.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub packages: Option<Level>,
 
     /// Permissions for pages.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub pages: Option<Level>,
 
     /// Permissions for ID tokens.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub id_token: Option<Level>,
+
+    /// Permissions for artifact attestations.
+    pub attestations: Option<Level>,
+
+    /// Permissions for discussions.
+    pub discussions: Option<Level>,
+
+    /// Permissions for code scanning alerts.
+    pub security_events: Option<Level>,
+
+    /// Permissions for classic projects.
+    pub repository_projects: Option<Level>,
+
+    /// Permissions for GitHub Models. Only `read` and `none` are supported.
+    pub models: Option<Level>,
+}
+
+/// The names of the scopes, in the order they are serialized.
+pub(crate) const SCOPES: [&str; 15] = [
+    "actions",
+    "contents",
+    "issues",
+    "pull-requests",
+    "deployments",
+    "checks",
+    "statuses",
+    "packages",
+    "pages",
+    "id-token",
+    "attestations",
+    "discussions",
+    "security-events",
+    "repository-projects",
+    "models",
+];
+
+impl Permissions {
+    /// Creates `Permissions` that grant read access to every scope.
+    pub fn read_all() -> Self {
+        Self { all: Some(Level::Read), ..Default::default() }
+    }
+
+    /// Creates `Permissions` that grant write access to every scope.
+    pub fn write_all() -> Self {
+        Self { all: Some(Level::Write), ..Default::default() }
+    }
+
+    /// Returns the level that the permissions grant to a scope, such as
+    /// `pull-requests`, or `None` if the scope isn't set.
+    pub fn level(&self, scope: &str) -> Option<Level> {
+        let index = SCOPES.iter().position(|name| *name == scope)?;
+        self.scopes()[index].or(self.all)
+    }
+
+    /// Combines two sets of permissions into the smallest set that grants
+    /// both, keeping the higher level of every scope.
+    pub fn union(&self, other: &Permissions) -> Permissions {
+        let mut union = Permissions { all: self.all.max(other.all), ..Default::default() };
+        for scope in SCOPES {
+            let level = self.level(scope).max(other.level(scope));
+            if level != union.all {
+                *union.scope_mut(scope).expect("scope is known") = level;
+            }
+        }
+        union
+    }
+
+    /// Returns the explicitly set level of every scope, in the order of
+    /// [`SCOPES`].
+    fn scopes(&self) -> [Option<Level>; 15] {
+        [
+            self.actions,
+            self.contents,
+            self.issues,
+            self.pull_requests,
+            self.deployments,
+            self.checks,
+            self.statuses,
+            self.packages,
+            self.pages,
+            self.id_token,
+            self.attestations,
+            self.discussions,
+            self.security_events,
+            self.repository_projects,
+            self.models,
+        ]
+    }
+
+    fn scope_mut(&mut self, scope: &str) -> Option<&mut Option<Level>> {
+        Some(match scope {
+            "actions" => &mut self.actions,
+            "contents" => &mut self.contents,
+            "issues" => &mut self.issues,
+            "pull-requests" => &mut self.pull_requests,
+            "deployments" => &mut self.deployments,
+            "checks" => &mut self.checks,
+            "statuses" => &mut self.statuses,
+            "packages" => &mut self.packages,
+            "pages" => &mut self.pages,
+            "id-token" => &mut self.id_token,
+            "attestations" => &mut self.attestations,
+            "discussions" => &mut self.discussions,
+            "security-events" => &mut self.security_events,
+            "repository-projects" => &mut self.repository_projects,
+            "models" => &mut self.models,
+            _ => return None,
+        })
+    }
+}
+
+impl Serialize for Permissions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let scopes = self.scopes();
+        let explicit = scopes.iter().any(Option::is_some);
+
+        match self.all {
+            Some(Level::Read) if !explicit => serializer.serialize_str("read-all"),
+            Some(Level::Write) if !explicit => serializer.serialize_str("write-all"),
+            _ => {
+                // Every scope is written out when `all` is combined with
+                // explicit scopes, since the shorthand can't express that.
+                let levels: Vec<_> = SCOPES
+                    .iter()
+                    .zip(scopes)
+                    .filter_map(|(scope, level)| {
+                        let level = match self.all {
+                            Some(Level::None) | None => level,
+                            all => level.or(all),
+                        };
+                        level.map(|level| (scope, level))
+                    })
+                    .collect();
+
+                let mut map = serializer.serialize_map(Some(levels.len()))?;
+                for (scope, level) in levels {
+                    map.serialize_entry(scope, &level)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PermissionsVisitor;
+
+        impl<'de> Visitor<'de> for PermissionsVisitor {
+            type Value = Permissions;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(
+                    f,
+                    "`read-all`, `write-all` or a mapping of scopes to levels"
+                )
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Permissions, E> {
+                match value {
+                    "read-all" => Ok(Permissions::read_all()),
+                    "write-all" => Ok(Permissions::write_all()),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Permissions, M::Error> {
+                let mut permissions = Permissions::default();
+                while let Some(scope) = map.next_key::<String>()? {
+                    let Some(level) = permissions.scope_mut(&scope) else {
+                        return Err(de::Error::unknown_field(&scope, &SCOPES));
+                    };
+                    *level = Some(map.next_value()?);
+                }
+                Ok(permissions)
+            }
+        }
+
+        deserializer.deserialize_any(PermissionsVisitor)
+    }
+}
+
+impl Workflow {
+    /// Returns the union of the permissions of the jobs: the least
+    /// privileges that the workflow needs to grant for every job to run.
+    /// Jobs that don't declare `permissions` are ignored. Returns `None`
+    /// when no job declares them.
+    pub fn least_privilege_permissions(&self) -> Option<Permissions> {
+        self.jobs
+            .iter()
+            .flat_map(|jobs| jobs.0.values())
+            .filter_map(|job| job.permissions.as_ref())
+            .fold(None, |union, permissions| {
+                Some(match union {
+                    Some(union) => permissions.union(&union),
+                    None => permissions.clone(),
+                })
+            })
+    }
 }
 
-/// Represents the level of permissions.
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+/// Represents the level of permissions, ordered from `none` to `write`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "kebab-case")]
 pub enum Level {
+    None,
     Read,
     Write,
-    #[default]
-    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Job, Step};
+
+    #[test]
+    fn shorthand_forms() {
+        let permissions = vec![
+            Permissions::read_all(),
+            Permissions::write_all(),
+            Permissions::default(),
+            Permissions::default()
+                .contents(Level::Read)
+                .security_events(Level::Write),
+        ];
+        let yaml = r#"- read-all
+- write-all
+- {}
+- contents: read
+  security-events: write
+"#;
+
+        assert_eq!(serde_yaml::to_string(&permissions).unwrap(), yaml);
+        assert_eq!(
+            serde_yaml::from_str::<Vec<Permissions>>(yaml).unwrap(),
+            permissions
+        );
+        assert!(serde_yaml::from_str::<Permissions>("read-some").is_err());
+        assert!(serde_yaml::from_str::<Permissions>("secrets: read").is_err());
+    }
+
+    #[test]
+    fn least_privilege_union() {
+        let build = Permissions::default().contents(Level::Read);
+        let release = Permissions::default()
+            .contents(Level::Write)
+            .pull_requests(Level::Write);
+        let attest = Permissions::default()
+            .id_token(Level::Write)
+            .attestations(Level::Write)
+            .contents(Level::None);
+
+        assert_eq!(
+            build.union(&release).union(&attest),
+            Permissions::default()
+                .contents(Level::Write)
+                .pull_requests(Level::Write)
+                .id_token(Level::Write)
+                .attestations(Level::Write)
+        );
+        assert_eq!(
+            Permissions::read_all().union(&release),
+            Permissions::read_all()
+                .contents(Level::Write)
+                .pull_requests(Level::Write)
+        );
+        assert_eq!(
+            Permissions::read_all().union(&Permissions::write_all()),
+            Permissions::write_all()
+        );
+
+        let build: Job = Job::new("Build").permissions(build);
+        let release_job: Job = Job::new("Release").permissions(release.clone());
+        let workflow = Workflow::new("CI")
+            .add_job("build", build)
+            .add_job("release", release_job)
+            .add_job(
+                "lint",
+                Job::new("Lint").add_step(Step::new("Lint").run("cargo clippy")),
+            );
+        assert_eq!(workflow.least_privilege_permissions(), Some(release));
+        assert_eq!(Workflow::new("CI").least_privilege_permissions(), None);
+    }
 }
@@ -0,0 +1,230 @@
+//!
+//! Pinning of actions to full commit SHAs, using a lock file kept in the
+//! repository.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::{Action, ActionRuns, StepValue, Workflow};
+
+/// Maps the actions that workflows use, as `owner/repo@ref`, to the commit
+/// SHAs they are pinned to. The lock file is a YAML mapping:
+///
+/// ```yaml
+/// actions/checkout@v5: 08c6903cd8c0fde910a37f88322edcfb5dd907a8
+/// ```
+///
+/// Actions in a subdirectory of a repository, such as
+/// `github/codeql-action/init@v3`, use the pin of the repository.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct ActionLock(BTreeMap<String, String>);
+
+impl ActionLock {
+    /// Creates an empty `ActionLock`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a lock file, checking that every action is pinned to a full
+    /// commit SHA.
+    pub fn parse(yml: &str) -> Result<Self> {
+        let lock: Self = serde_yaml::from_str(yml)?;
+        if let Some((action, sha)) = lock.0.iter().find(|(_, sha)| !is_sha(sha)) {
+            return Err(Error::InvalidActionPin { action: action.clone(), sha: sha.clone() });
+        }
+        Ok(lock)
+    }
+
+    /// Converts the `ActionLock` to the YAML of a lock file.
+    pub fn to_string(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Pins an action, given as `owner/repo@ref`, to a commit SHA.
+    pub fn add_pin<A: ToString, S: ToString>(mut self, action: A, sha: S) -> Self {
+        self.0.insert(action.to_string(), sha.to_string());
+        self
+    }
+
+    /// Returns the SHA that an action, given as `owner/repo@ref`, is pinned
+    /// to.
+    pub fn get(&self, action: &str) -> Option<&str> {
+        self.0.get(action).map(String::as_str)
+    }
+
+    /// Rewrites every `uses` of a workflow to its pinned SHA.
+    pub(crate) fn pin_workflow(&self, workflow: &mut Workflow) -> Result<Vec<Pin>> {
+        let mut pins = Pins::default();
+        for job in workflow
+            .jobs
+            .iter_mut()
+            .flat_map(|jobs| jobs.0.values_mut())
+        {
+            if let Some(uses) = &mut job.uses {
+                pins.pin(self, uses);
+            }
+            for step in job.steps.iter_mut().flatten() {
+                pins.pin_step(self, step);
+            }
+        }
+        pins.finish()
+    }
+
+    /// Rewrites every `uses` of a composite action to its pinned SHA.
+    pub(crate) fn pin_action(&self, action: &mut Action) -> Result<Vec<Pin>> {
+        let mut pins = Pins::default();
+        match &mut action.runs {
+            ActionRuns::Composite { steps } => {
+                for step in steps {
+                    pins.pin_step(self, step);
+                }
+            }
+        }
+        pins.finish()
+    }
+}
+
+/// A `uses` that was pinned, and the ref it was pinned from.
+pub(crate) struct Pin {
+    uses: String,
+    ref_: String,
+}
+
+#[derive(Default)]
+struct Pins {
+    pinned: Vec<Pin>,
+    unpinned: Vec<String>,
+}
+
+impl Pins {
+    fn pin_step(&mut self, lock: &ActionLock, step: &mut StepValue) {
+        if let Some(uses) = &mut step.uses {
+            self.pin(lock, uses);
+        }
+    }
+
+    fn pin(&mut self, lock: &ActionLock, uses: &mut String) {
+        // Local actions and workflows, and Docker images, aren't pinned.
+        if uses.starts_with("./") || uses.starts_with("docker://") {
+            return;
+        }
+        let Some((action, ref_)) = uses.split_once('@') else {
+            self.unpinned.push(uses.clone());
+            return;
+        };
+        if is_sha(ref_) {
+            return;
+        }
+
+        let repo = action.splitn(3, '/').take(2).collect::<Vec<_>>().join("/");
+        let sha = lock
+            .get(uses)
+            .or_else(|| lock.get(&format!("{repo}@{ref_}")));
+        match sha {
+            Some(sha) => {
+                let pin = Pin { uses: format!("{action}@{sha}"), ref_: ref_.to_string() };
+                *uses = pin.uses.clone();
+                self.pinned.push(pin);
+            }
+            None => self.unpinned.push(uses.clone()),
+        }
+    }
+
+    fn finish(mut self) -> Result<Vec<Pin>> {
+        if self.unpinned.is_empty() {
+            Ok(self.pinned)
+        } else {
+            self.unpinned.sort();
+            self.unpinned.dedup();
+            Err(Error::UnpinnedActions(self.unpinned))
+        }
+    }
+}
+
+/// Adds the ref that each pinned `uses` was pinned from as a trailing
+/// comment, such as `uses: actions/checkout@<sha> # v5`.
+pub(crate) fn annotate(yaml: &str, pins: &[Pin]) -> String {
+    if pins.is_empty() {
+        return yaml.to_string();
+    }
+
+    yaml.split_inclusive('\n')
+        .map(|line| {
+            let content = line.trim_end_matches('\n');
+            let value = content
+                .trim_start()
+                .trim_start_matches("- ")
+                .strip_prefix("uses: ")
+                .map(|value| value.trim_matches(['\'', '"']));
+            match value.and_then(|value| pins.iter().find(|pin| pin.uses == value)) {
+                Some(pin) => format!("{content} # {}{}", pin.ref_, &line[content.len()..]),
+                None => line.to_string(),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn is_sha(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::actions::Checkout;
+    use crate::{Job, Step};
+
+    const CHECKOUT: &str = "08c6903cd8c0fde910a37f88322edcfb5dd907a8";
+    const CODEQL: &str = "df559355d593797519d70b90fc8edd5db049e7a2";
+
+    #[test]
+    fn pins_uses_to_shas() {
+        let lock = ActionLock::parse(&format!(
+            "actions/checkout@v5: {CHECKOUT}\ngithub/codeql-action@v3: {CODEQL}\n"
+        ))
+        .unwrap();
+        let mut workflow = Workflow::new("CI")
+            .add_job(
+                "analyze",
+                Job::new("Analyze")
+                    .add_step(Checkout::default().fetch_depth(0u32))
+                    .add_step(Step::new("Init").uses("github", "codeql-action/init", "v3")),
+            )
+            .add_job(
+                "release",
+                Job::new("Release").uses_local(".github/workflows/release.yml"),
+            );
+
+        let pins = lock.pin_workflow(&mut workflow).unwrap();
+        let yaml = annotate(&workflow.to_string().unwrap(), &pins);
+
+        assert!(yaml.contains(&format!("uses: actions/checkout@{CHECKOUT} # v5\n")));
+        assert!(yaml.contains(&format!("uses: github/codeql-action/init@{CODEQL} # v3\n")));
+        assert!(yaml.contains("uses: ./.github/workflows/release.yml\n"));
+    }
+
+    #[test]
+    fn rejects_unpinned_actions() {
+        let lock = ActionLock::new().add_pin("actions/checkout@v5", CHECKOUT);
+        let mut workflow = Workflow::new("CI").add_job(
+            "build",
+            Job::new("Build")
+                .add_step(Step::checkout())
+                .add_step(Step::new("Cache").uses("actions", "cache", "v4")),
+        );
+
+        let Err(Error::UnpinnedActions(actions)) = lock.pin_workflow(&mut workflow) else {
+            panic!("expected an error for the unpinned action");
+        };
+        assert_eq!(actions, vec!["actions/cache@v4".to_string()]);
+        assert!(matches!(
+            ActionLock::parse("actions/checkout@v5: v5"),
+            Err(Error::InvalidActionPin { .. })
+        ));
+    }
+}
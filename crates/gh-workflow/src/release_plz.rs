@@ -2,7 +2,7 @@
 //! Docs: <https://github.com/release-plz/action>
 use derive_setters::Setters;
 
-use crate::{Step, Use};
+use crate::{Level, Permissions, Step, Use};
 
 #[derive(Clone, Default, Setters)]
 #[setters(strip_option, into)]
@@ -73,7 +73,13 @@ impl std::fmt::Display for Backend {
 
 impl From<Release> for Step<Use> {
     fn from(value: Release) -> Self {
-        let mut step = Step::new("Release Plz").uses("release-plz", "action", "v0.5");
+        let mut step = Step::new("Release Plz")
+            .uses("release-plz", "action", "v0.5")
+            .require_permissions(
+                Permissions::default()
+                    .contents(Level::Write)
+                    .pull_requests(Level::Write),
+            );
 
         if let Some(command) = value.command {
             step = step.add_with(("command", command.to_string()));
@@ -0,0 +1,334 @@
+//!
+//! Runner types that select the machine a job runs on.
+
+use std::fmt::{Display, Formatter};
+
+use derive_setters::Setters;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::ctx::Context;
+use crate::Expression;
+
+/// Represents the `runs-on` of a job: a single runner label, a set of labels
+/// that a runner must all have, or a runner group.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RunsOn {
+    Label(RunnerLabel),
+    Labels(Vec<RunnerLabel>),
+    Group(RunnerGroup),
+}
+
+impl Default for RunsOn {
+    fn default() -> Self {
+        RunsOn::Label(HostedRunner::UbuntuLatest.into())
+    }
+}
+
+impl RunsOn {
+    /// Creates a `runs-on` that selects a self-hosted runner with all of the
+    /// given labels.
+    pub fn self_hosted<L: Into<RunnerLabel>>(labels: impl IntoIterator<Item = L>) -> Self {
+        let mut all = vec![RunnerLabel::SelfHosted];
+        all.extend(labels.into_iter().map(Into::into));
+        RunsOn::Labels(all)
+    }
+
+    /// Creates a `runs-on` that selects a runner from the given group.
+    pub fn group<S: ToString>(group: S) -> Self {
+        RunsOn::Group(RunnerGroup::new(group))
+    }
+}
+
+impl<T: Into<RunnerLabel>> From<T> for RunsOn {
+    fn from(value: T) -> Self {
+        RunsOn::Label(value.into())
+    }
+}
+
+impl<T: Into<RunnerLabel>> From<Vec<T>> for RunsOn {
+    fn from(value: Vec<T>) -> Self {
+        RunsOn::Labels(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<RunnerGroup> for RunsOn {
+    fn from(value: RunnerGroup) -> Self {
+        RunsOn::Group(value)
+    }
+}
+
+/// Represents a runner group, used to run jobs on larger or self-hosted
+/// runners shared within an organization.
+#[derive(Debug, Setters, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct RunnerGroup {
+    /// The name of the runner group.
+    pub group: String,
+
+    /// The labels a runner of the group must have. A single label is accepted
+    /// when parsing.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "one_or_many"
+    )]
+    #[setters(skip)]
+    pub labels: Vec<RunnerLabel>,
+}
+
+impl RunnerGroup {
+    /// Creates a new `RunnerGroup` with the specified name.
+    pub fn new<S: ToString>(group: S) -> Self {
+        Self { group: group.to_string(), labels: Vec::new() }
+    }
+
+    /// Adds a label that the runner must have.
+    pub fn add_label<L: Into<RunnerLabel>>(mut self, label: L) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RunnerLabel>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(RunnerLabel),
+        Many(Vec<RunnerLabel>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(label) => vec![label],
+        OneOrMany::Many(labels) => labels,
+    })
+}
+
+/// Represents a single runner label.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum RunnerLabel {
+    /// A runner image hosted by GitHub.
+    Hosted(HostedRunner),
+
+    /// The `self-hosted` label that every self-hosted runner has.
+    SelfHosted,
+
+    /// A custom label, such as the name of a larger runner or a label added
+    /// to a self-hosted runner.
+    Custom(String),
+
+    /// An expression that evaluates to a label at runtime, such as
+    /// `${{ matrix.os }}`.
+    Expression(Expression),
+}
+
+impl Display for RunnerLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunnerLabel::Hosted(runner) => write!(f, "{runner}"),
+            RunnerLabel::SelfHosted => write!(f, "self-hosted"),
+            RunnerLabel::Custom(label) => write!(f, "{label}"),
+            RunnerLabel::Expression(expression) => write!(f, "{}", expression.0),
+        }
+    }
+}
+
+impl From<String> for RunnerLabel {
+    fn from(value: String) -> Self {
+        if value.trim_start().starts_with("${{") {
+            RunnerLabel::Expression(Expression(value))
+        } else if value == "self-hosted" {
+            RunnerLabel::SelfHosted
+        } else if let Some(runner) = HostedRunner::parse(&value) {
+            RunnerLabel::Hosted(runner)
+        } else {
+            RunnerLabel::Custom(value)
+        }
+    }
+}
+
+impl From<&str> for RunnerLabel {
+    fn from(value: &str) -> Self {
+        RunnerLabel::from(value.to_string())
+    }
+}
+
+impl From<RunnerLabel> for String {
+    fn from(value: RunnerLabel) -> Self {
+        value.to_string()
+    }
+}
+
+impl From<HostedRunner> for RunnerLabel {
+    fn from(value: HostedRunner) -> Self {
+        RunnerLabel::Hosted(value)
+    }
+}
+
+impl From<Expression> for RunnerLabel {
+    fn from(value: Expression) -> Self {
+        RunnerLabel::Expression(value)
+    }
+}
+
+impl<A> From<Context<A>> for RunnerLabel {
+    fn from(value: Context<A>) -> Self {
+        RunnerLabel::Expression(value.into())
+    }
+}
+
+/// Represents a runner image hosted by GitHub.
+/// Docs: <https://docs.github.com/en/actions/using-github-hosted-runners/about-github-hosted-runners>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostedRunner {
+    UbuntuLatest,
+    Ubuntu2404,
+    Ubuntu2204,
+    Ubuntu2404Arm,
+    Ubuntu2204Arm,
+    WindowsLatest,
+    Windows2025,
+    Windows2022,
+    Windows11Arm,
+    MacosLatest,
+    Macos15,
+    Macos14,
+    Macos13,
+    MacosLatestLarge,
+    Macos15Large,
+    Macos14Large,
+    Macos13Large,
+    MacosLatestXlarge,
+    Macos15Xlarge,
+    Macos14Xlarge,
+    Macos13Xlarge,
+}
+
+impl HostedRunner {
+    const ALL: [HostedRunner; 21] = [
+        HostedRunner::UbuntuLatest,
+        HostedRunner::Ubuntu2404,
+        HostedRunner::Ubuntu2204,
+        HostedRunner::Ubuntu2404Arm,
+        HostedRunner::Ubuntu2204Arm,
+        HostedRunner::WindowsLatest,
+        HostedRunner::Windows2025,
+        HostedRunner::Windows2022,
+        HostedRunner::Windows11Arm,
+        HostedRunner::MacosLatest,
+        HostedRunner::Macos15,
+        HostedRunner::Macos14,
+        HostedRunner::Macos13,
+        HostedRunner::MacosLatestLarge,
+        HostedRunner::Macos15Large,
+        HostedRunner::Macos14Large,
+        HostedRunner::Macos13Large,
+        HostedRunner::MacosLatestXlarge,
+        HostedRunner::Macos15Xlarge,
+        HostedRunner::Macos14Xlarge,
+        HostedRunner::Macos13Xlarge,
+    ];
+
+    /// Returns the label GitHub uses for the image.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HostedRunner::UbuntuLatest => "ubuntu-latest",
+            HostedRunner::Ubuntu2404 => "ubuntu-24.04",
+            HostedRunner::Ubuntu2204 => "ubuntu-22.04",
+            HostedRunner::Ubuntu2404Arm => "ubuntu-24.04-arm",
+            HostedRunner::Ubuntu2204Arm => "ubuntu-22.04-arm",
+            HostedRunner::WindowsLatest => "windows-latest",
+            HostedRunner::Windows2025 => "windows-2025",
+            HostedRunner::Windows2022 => "windows-2022",
+            HostedRunner::Windows11Arm => "windows-11-arm",
+            HostedRunner::MacosLatest => "macos-latest",
+            HostedRunner::Macos15 => "macos-15",
+            HostedRunner::Macos14 => "macos-14",
+            HostedRunner::Macos13 => "macos-13",
+            HostedRunner::MacosLatestLarge => "macos-latest-large",
+            HostedRunner::Macos15Large => "macos-15-large",
+            HostedRunner::Macos14Large => "macos-14-large",
+            HostedRunner::Macos13Large => "macos-13-large",
+            HostedRunner::MacosLatestXlarge => "macos-latest-xlarge",
+            HostedRunner::Macos15Xlarge => "macos-15-xlarge",
+            HostedRunner::Macos14Xlarge => "macos-14-xlarge",
+            HostedRunner::Macos13Xlarge => "macos-13-xlarge",
+        }
+    }
+
+    /// Finds the image with the given label.
+    pub fn parse(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|runner| runner.as_str() == label)
+    }
+}
+
+impl Display for HostedRunner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn runs_on_forms() {
+        let runs_on: Vec<RunsOn> = vec![
+            HostedRunner::Ubuntu2404Arm.into(),
+            RunsOn::self_hosted(["linux", "x64"]),
+            RunsOn::group("larger-runners"),
+            RunnerGroup::new("larger-runners")
+                .add_label("ubuntu-24.04-16core")
+                .into(),
+            Context::matrix().get("os").into(),
+        ];
+
+        assert_eq!(
+            serde_yaml::to_string(&runs_on).unwrap(),
+            r#"- ubuntu-24.04-arm
+- - self-hosted
+  - linux
+  - x64
+- group: larger-runners
+- group: larger-runners
+  labels:
+  - ubuntu-24.04-16core
+- ${{ matrix.os }}
+"#
+        );
+    }
+
+    #[test]
+    fn parse_labels() {
+        let runs_on: Vec<RunsOn> = serde_yaml::from_str(
+            r#"
+- macos-14-xlarge
+- benchmarking-runner
+- "${{ matrix.os || 'ubuntu-latest' }}"
+- [self-hosted, gpu]
+- group: larger-runners
+  labels: ubuntu-24.04-16core
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            runs_on,
+            vec![
+                RunsOn::Label(RunnerLabel::Hosted(HostedRunner::Macos14Xlarge)),
+                RunsOn::Label(RunnerLabel::Custom("benchmarking-runner".into())),
+                RunsOn::Label(RunnerLabel::Expression(Expression::new(
+                    "${{ matrix.os || 'ubuntu-latest' }}"
+                ))),
+                RunsOn::self_hosted(["gpu"]),
+                RunsOn::Group(RunnerGroup::new("larger-runners").add_label("ubuntu-24.04-16core")),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //!
 //! Step-related structures and implementations for GitHub workflow steps.
 
+use std::sync::atomic::{self, AtomicUsize};
 use std::time::Duration;
 
 use derive_setters::Setters;
@@ -9,8 +10,10 @@ use merge::Merge;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::actions::Checkout;
+use crate::ctx::Context;
 use crate::toolchain::{Abi, Arch, Component, System, Target, Toolchain, Vendor, Version};
-use crate::{private, Artifacts, Env, Expression, RetryStrategy};
+use crate::{private, Artifacts, Env, Expression, Permissions, RetryStrategy};
 
 /// Represents a step in the workflow.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -100,13 +103,12 @@ impl Input {
 }
 
 /// Represents a step value in the workflow.
-#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq, Merge)]
+#[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, Merge)]
 #[serde(rename_all = "kebab-case")]
 #[setters(
     strip_option,
     into,
-    generate_delegates(ty = "Step<Run>", field = "value"),
-    generate_delegates(ty = "Step<Use>", field = "value")
+    generate_delegates(ty = "Step<T>", generics = "<T: StepType>", field = "value")
 )]
 pub struct StepValue {
     /// The ID of the step.
@@ -162,6 +164,59 @@ pub struct StepValue {
     /// Artifacts produced by the step.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub artifacts: Option<Artifacts>,
+
+    /// The `GITHUB_TOKEN` permissions that the step needs, used to infer the
+    /// permissions of its job.
+    #[serde(skip)]
+    #[setters(skip)]
+    #[merge(strategy = merge_permissions)]
+    pub(crate) permissions: Option<Permissions>,
+}
+
+impl PartialEq for StepValue {
+    /// Compares the steps as they are written to the workflow, leaving out
+    /// the permissions that are only used to infer the ones of the job.
+    fn eq(&self, other: &Self) -> bool {
+        let StepValue {
+            id,
+            name,
+            if_condition,
+            uses,
+            with,
+            run,
+            shell,
+            envs,
+            timeout_minutes,
+            continue_on_error,
+            working_directory,
+            retry,
+            artifacts,
+            permissions: _,
+        } = self;
+
+        *id == other.id
+            && *name == other.name
+            && *if_condition == other.if_condition
+            && *uses == other.uses
+            && *with == other.with
+            && *run == other.run
+            && *shell == other.shell
+            && *envs == other.envs
+            && *timeout_minutes == other.timeout_minutes
+            && *continue_on_error == other.continue_on_error
+            && *working_directory == other.working_directory
+            && *retry == other.retry
+            && *artifacts == other.artifacts
+    }
+}
+
+impl Eq for StepValue {}
+
+fn merge_permissions(left: &mut Option<Permissions>, right: Option<Permissions>) {
+    *left = match (left.take(), right) {
+        (Some(left), Some(right)) => Some(left.union(&right)),
+        (left, right) => left.or(right),
+    };
 }
 
 impl StepValue {
@@ -204,6 +259,80 @@ impl<T> Step<T> {
         self.value = self.value.timeout_minutes(duration.as_secs() as u32 / 60);
         self
     }
+
+    /// Declares the `GITHUB_TOKEN` permissions that the step needs. See
+    /// [`Job::infer_permissions`](crate::Job::infer_permissions).
+    pub fn require_permissions(mut self, permissions: Permissions) -> Self {
+        merge_permissions(&mut self.value.permissions, Some(permissions));
+        self
+    }
+
+    /// Returns a reference to the output `name` of this step, which renders as
+    /// `steps.<id>.outputs.<name>`. If the step doesn't have an `id` yet, one
+    /// is derived from its name. Steps without a name get an id derived from
+    /// the action they use and their position in the job when the workflow
+    /// is generated, such as `checkout-1`.
+    pub fn output(&mut self, name: impl Into<String>) -> Context<String> {
+        let value = &mut self.value;
+        let id = value.id.get_or_insert_with(|| match value.name.as_deref() {
+            Some(name) => step_id(name),
+            None => {
+                let count = UNNAMED_STEPS.fetch_add(1, atomic::Ordering::Relaxed);
+                format!("<step:{count}>")
+            }
+        });
+
+        Context::steps().get(id.clone()).outputs().get(name)
+    }
+}
+
+/// Counts the unnamed steps that were given a placeholder id, to keep the
+/// placeholders unique until the steps are numbered by their position.
+static UNNAMED_STEPS: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the placeholder ids that [`Step::output`] gives unnamed steps with
+/// ids derived from the action they use and their position, and returns the
+/// `steps.<placeholder>` references to replace with `steps.<id>`.
+pub(crate) fn resolve_step_ids(steps: &mut [StepValue]) -> Vec<(String, String)> {
+    let mut ids = Vec::new();
+    for (index, step) in steps.iter_mut().enumerate() {
+        let Some(placeholder) = step.id.take_if(|id| id.starts_with("<step:")) else {
+            continue;
+        };
+        let action = step
+            .uses
+            .as_deref()
+            .and_then(|uses| uses.split('@').next())
+            .and_then(|action| action.rsplit('/').next())
+            .unwrap_or_default();
+        let id = format!("{}-{}", step_id(action), index + 1);
+
+        ids.push((format!("steps.{placeholder}"), format!("steps.{id}")));
+        step.id = Some(id);
+    }
+    ids
+}
+
+/// Derives a step id from a step name, e.g. `Get version` becomes
+/// `get-version`.
+fn step_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    let id = id.trim_end_matches('-');
+
+    if id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        id.to_string()
+    } else if id.is_empty() {
+        "step".to_string()
+    } else {
+        format!("step-{id}")
+    }
 }
 
 impl Step<()> {
@@ -232,9 +361,10 @@ impl Step<()> {
 
 /// Represents a step that uses an action.
 impl Step<Use> {
-    /// Creates a step pointing to the default GitHub's Checkout Action.
+    /// Creates a step pointing to the default GitHub's Checkout Action. Use
+    /// [`Checkout`] to set its inputs.
     pub fn checkout() -> Step<Use> {
-        Step::new("Checkout Code").uses("actions", "checkout", "v5")
+        Checkout::default().into()
     }
 
     /// Adds a new input to the step.
@@ -305,6 +435,173 @@ impl Step<Toolchain> {
 impl StepType for Toolchain {
     fn to_value(s: Step<Self>) -> StepValue {
         let step: Step<Use> = s.marker.into();
-        StepValue::from(step)
+        let mut value = s.value;
+        value.merge(StepValue::from(step));
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::release_plz::Release;
+    use crate::{Job, JobType, Level, RunJob};
+
+    #[test]
+    fn output_sets_missing_id() {
+        let mut step = Step::new("Get version").run("echo version=1.0 >> $GITHUB_OUTPUT");
+        let version = step.output("version");
+
+        assert_eq!(
+            version.to_string(),
+            "${{ steps.get-version.outputs.version }}"
+        );
+        assert_eq!(step.value.id, Some("get-version".to_string()));
+    }
+
+    #[test]
+    fn output_keeps_existing_id() {
+        let mut step = Step::new("Get version").run("./version.sh").id("v");
+
+        assert_eq!(
+            step.output("version").to_string(),
+            "${{ steps.v.outputs.version }}"
+        );
+        assert_eq!(step.output("tag").to_string(), "${{ steps.v.outputs.tag }}");
+    }
+
+    #[test]
+    fn step_ids() {
+        assert_eq!(step_id("Build & Test (nightly)"), "build-test-nightly");
+        assert_eq!(step_id("1st step"), "step-1st-step");
+        assert_eq!(step_id(""), "step");
+    }
+
+    #[test]
+    fn toolchain_keeps_step_settings() {
+        let mut stable = Step::toolchain().add_stable();
+        let mut nightly = Step::toolchain().add_nightly();
+        let stable_key = stable.output("cachekey");
+        let nightly_key = nightly.output("cachekey");
+        let stable_id = stable.value.id.clone().unwrap();
+        let nightly_id = nightly.value.id.clone().unwrap();
+
+        assert_ne!(stable_id, nightly_id);
+        assert_eq!(
+            stable_key.to_string(),
+            format!("${{{{ steps.{stable_id}.outputs.cachekey }}}}")
+        );
+        assert_eq!(
+            nightly_key.to_string(),
+            format!("${{{{ steps.{nightly_id}.outputs.cachekey }}}}")
+        );
+
+        let job: Job = Job::new("Build")
+            .add_step(Step::checkout())
+            .add_step(stable)
+            .add_step(nightly)
+            .add_step(Step::new("Print").run(format!("echo {stable_key}")));
+        let mut steps = RunJob::to_value(job).steps.unwrap();
+        let ids = resolve_step_ids(&mut steps);
+
+        let step_ids: Vec<_> = steps.iter().map(|step| step.id.as_deref()).collect();
+        assert_eq!(
+            step_ids,
+            vec![
+                None,
+                Some("setup-rust-toolchain-2"),
+                Some("setup-rust-toolchain-3"),
+                None
+            ]
+        );
+        assert_eq!(
+            ids,
+            vec![
+                (
+                    format!("steps.{stable_id}"),
+                    "steps.setup-rust-toolchain-2".to_string()
+                ),
+                (
+                    format!("steps.{nightly_id}"),
+                    "steps.setup-rust-toolchain-3".to_string()
+                ),
+            ]
+        );
+
+        let mut stable = Step::toolchain().add_stable();
+        stable.output("cachekey");
+        let id = stable.value.id.clone();
+        let value = Toolchain::to_value(stable);
+        assert_eq!(value.id, id);
+        assert_eq!(value.name, Some("Setup Rust Toolchain".to_string()));
+    }
+
+    #[test]
+    fn infers_job_permissions() {
+        let job: Job = Job::new("Release")
+            .add_step(Step::checkout())
+            .add_step(Step::toolchain().add_stable())
+            .add_step(Release::default())
+            .add_step(
+                Step::new("Comment")
+                    .run("gh pr comment --body done")
+                    .require_permissions(Permissions::default().issues(Level::Write)),
+            );
+
+        assert_eq!(
+            job.required_permissions(),
+            Some(
+                Permissions::default()
+                    .contents(Level::Write)
+                    .pull_requests(Level::Write)
+                    .issues(Level::Write)
+            )
+        );
+
+        let build: Job = Job::new("Build")
+            .permissions(Permissions::default().packages(Level::Read))
+            .add_step(Step::checkout())
+            .add_step(Step::new("Build").run("cargo build"))
+            .infer_permissions();
+        assert_eq!(
+            RunJob::to_value(build).permissions,
+            Some(
+                Permissions::default()
+                    .contents(Level::Read)
+                    .packages(Level::Read)
+            )
+        );
+
+        let empty: Job = Job::new("Empty")
+            .add_step(
+                Step::new("Echo")
+                    .run("echo hi")
+                    .require_permissions(Permissions::default()),
+            )
+            .infer_permissions();
+        assert_eq!(
+            RunJob::to_value(empty).permissions,
+            Some(Permissions::default())
+        );
+    }
+
+    #[test]
+    fn keeps_default_permissions_of_unknown_steps() {
+        let script: Job = Job::new("Script")
+            .add_step(Step::checkout())
+            .add_step(Step::new("Push").run("git push"));
+        assert_eq!(script.required_permissions(), None);
+        assert_eq!(
+            RunJob::to_value(script.infer_permissions()).permissions,
+            None
+        );
+
+        let action: Job = Job::new("Action")
+            .add_step(Step::checkout())
+            .add_step(Step::new("Deploy").uses("some-org", "deploy", "v1"))
+            .infer_permissions();
+        assert_eq!(RunJob::to_value(action).permissions, None);
     }
 }
@@ -2,9 +2,14 @@
 //! Strategy types for GitHub workflow job execution.
 
 use derive_setters::Setters;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::ctx::Context;
+use crate::error::{Error, Result};
+use crate::Expression;
+
 /// Represents the strategy for running jobs.
 #[derive(Debug, Setters, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -12,7 +17,7 @@ use serde_json::Value;
 pub struct Strategy {
     /// The matrix for job execution.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub matrix: Option<Value>,
+    pub matrix: Option<Matrix>,
 
     /// Whether to fail fast on errors.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -22,3 +27,341 @@ pub struct Strategy {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_parallel: Option<u32>,
 }
+
+/// Represents a matrix of job configurations. Every combination of the values
+/// of its axes runs as a separate job.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(from = "MatrixRepr", into = "MatrixRepr")]
+pub struct Matrix {
+    /// The axes of the matrix, with either a list of values or an expression
+    /// that evaluates to one.
+    pub axes: IndexMap<String, Value>,
+
+    /// Additional combinations, or extra values for existing combinations.
+    /// An `include` computed by an expression is kept in `axes` as is.
+    pub include: Vec<IndexMap<String, Value>>,
+
+    /// Combinations that should not run. Like `include`, an `exclude`
+    /// computed by an expression is kept in `axes`.
+    pub exclude: Vec<IndexMap<String, Value>>,
+
+    /// An expression that evaluates to the whole matrix at runtime. When set,
+    /// the axes, includes and excludes are ignored.
+    pub expression: Option<Expression>,
+}
+
+/// Types that can be used as values of a matrix axis, along with the type of
+/// the `matrix` context that reads them.
+pub trait AxisValue: Into<Value> {
+    type Context;
+}
+
+impl AxisValue for &str {
+    type Context = String;
+}
+
+impl AxisValue for String {
+    type Context = String;
+}
+
+impl AxisValue for bool {
+    type Context = bool;
+}
+
+impl AxisValue for i32 {
+    type Context = i32;
+}
+
+impl AxisValue for i64 {
+    type Context = i64;
+}
+
+impl AxisValue for u32 {
+    type Context = u32;
+}
+
+impl AxisValue for f64 {
+    type Context = f64;
+}
+
+impl Matrix {
+    /// Creates a matrix that is computed at runtime, typically by parsing the
+    /// output of another job with `fromJSON`.
+    pub fn dynamic<E: Into<Expression>>(expression: E) -> Self {
+        Self { expression: Some(expression.into()), ..Default::default() }
+    }
+
+    /// Adds an axis to the matrix, and returns the `matrix.<name>` context
+    /// that reads the value of the axis for the current job.
+    pub fn add_axis<T: AxisValue>(
+        &mut self,
+        name: impl Into<String>,
+        values: impl IntoIterator<Item = T>,
+    ) -> Context<T::Context> {
+        let name = name.into();
+        let values = values.into_iter().map(Into::into).collect();
+        self.axes.insert(name.clone(), Value::Array(values));
+
+        Context::matrix().select(name)
+    }
+
+    /// Adds an axis whose values are computed at runtime by an expression,
+    /// and returns the `matrix.<name>` context that reads it.
+    pub fn add_dynamic_axis<E: Into<Expression>>(
+        &mut self,
+        name: impl Into<String>,
+        values: E,
+    ) -> Context<String> {
+        let name = name.into();
+        self.axes
+            .insert(name.clone(), Value::String(values.into().0));
+
+        Context::matrix().get(name)
+    }
+
+    /// Adds a combination to the matrix. Fails if the entry references an
+    /// axis that the matrix doesn't have.
+    pub fn add_include<K: ToString, V: Into<Value>>(
+        &mut self,
+        entry: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<&mut Self> {
+        let entry = self.entry(entry)?;
+        self.include.push(entry);
+        Ok(self)
+    }
+
+    /// Excludes a combination from the matrix. Fails if the entry references
+    /// an axis that the matrix doesn't have.
+    pub fn add_exclude<K: ToString, V: Into<Value>>(
+        &mut self,
+        entry: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<&mut Self> {
+        let entry = self.entry(entry)?;
+        self.exclude.push(entry);
+        Ok(self)
+    }
+
+    fn entry<K: ToString, V: Into<Value>>(
+        &self,
+        entry: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<IndexMap<String, Value>> {
+        entry
+            .into_iter()
+            .map(|(key, value)| {
+                let key = key.to_string();
+                if self.axes.contains_key(&key) {
+                    Ok((key, value.into()))
+                } else {
+                    Err(Error::UnknownMatrixAxis(key))
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<Value> for Matrix {
+    /// Converts a matrix written as JSON, such as with `json!`. A string is
+    /// an expression, and the `include` and `exclude` lists of an object are
+    /// read as such while its other properties are axes.
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(expression) => Matrix::dynamic(expression),
+            Value::Object(object) => {
+                let mut matrix = Matrix::default();
+                for (key, value) in object {
+                    let entries = serde_json::from_value(value.clone()).ok();
+                    match (key.as_str(), entries) {
+                        ("include", Some(entries)) => matrix.include = entries,
+                        ("exclude", Some(entries)) => matrix.exclude = entries,
+                        _ => {
+                            matrix.axes.insert(key, value);
+                        }
+                    }
+                }
+                matrix
+            }
+            // Not a valid matrix, but written out as is so that GitHub
+            // reports it.
+            value => Matrix::dynamic(value.to_string()),
+        }
+    }
+}
+
+/// The serialized form of a [`Matrix`].
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum MatrixRepr {
+    Expression(Expression),
+    Axes {
+        #[serde(flatten)]
+        axes: IndexMap<String, Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        include: Option<MatrixEntries>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exclude: Option<MatrixEntries>,
+    },
+}
+
+/// The `include` or `exclude` list of a serialized [`Matrix`].
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum MatrixEntries {
+    List(Vec<IndexMap<String, Value>>),
+    Expression(String),
+}
+
+impl From<MatrixRepr> for Matrix {
+    fn from(value: MatrixRepr) -> Self {
+        match value {
+            MatrixRepr::Expression(expression) => Matrix::dynamic(expression),
+            MatrixRepr::Axes { mut axes, include, exclude } => {
+                let mut entries = |key: &str, value: Option<MatrixEntries>| match value {
+                    Some(MatrixEntries::List(entries)) => entries,
+                    Some(MatrixEntries::Expression(expression)) => {
+                        axes.insert(key.to_string(), Value::String(expression));
+                        Vec::new()
+                    }
+                    None => Vec::new(),
+                };
+                let include = entries("include", include);
+                let exclude = entries("exclude", exclude);
+                Matrix { axes, include, exclude, expression: None }
+            }
+        }
+    }
+}
+
+impl From<Matrix> for MatrixRepr {
+    fn from(value: Matrix) -> Self {
+        match value.expression {
+            Some(expression) => MatrixRepr::Expression(expression),
+            None => {
+                let entries =
+                    |entries: Vec<_>| (!entries.is_empty()).then_some(MatrixEntries::List(entries));
+                MatrixRepr::Axes {
+                    axes: value.axes,
+                    include: entries(value.include),
+                    exclude: entries(value.exclude),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn matrix_axes() {
+        let mut matrix = Matrix::default();
+        let os = matrix.add_axis("os", ["ubuntu-latest", "windows-latest"]);
+        let experimental = matrix.add_axis("experimental", [false]);
+
+        matrix
+            .add_include([
+                ("os", Value::from("macos-latest")),
+                ("experimental", true.into()),
+            ])
+            .unwrap()
+            .add_exclude([("os", "windows-latest")])
+            .unwrap();
+
+        assert_eq!(os.to_string(), "${{ matrix.os }}");
+        assert_eq!(
+            experimental.eq(true.into()).to_string(),
+            "${{ matrix.experimental == true }}"
+        );
+        assert_eq!(
+            serde_yaml::to_string(&Strategy::default().matrix(matrix)).unwrap(),
+            r#"matrix:
+  os:
+  - ubuntu-latest
+  - windows-latest
+  experimental:
+  - false
+  include:
+  - os: macos-latest
+    experimental: true
+  exclude:
+  - os: windows-latest
+"#
+        );
+    }
+
+    #[test]
+    fn matrix_from_json() {
+        let mut matrix = Matrix::default();
+        let node = matrix.add_axis("node", [18, 20]);
+        matrix.add_include([("node", 22)]).unwrap();
+
+        assert_eq!(node.to_string(), "${{ matrix.node }}");
+        assert_eq!(
+            Matrix::from(serde_json::json!({ "node": [18, 20], "include": [{ "node": 22 }] })),
+            matrix
+        );
+        assert_eq!(
+            Strategy::default().matrix(serde_json::json!(
+                "${{ fromJSON(needs.setup.outputs.matrix) }}"
+            )),
+            Strategy::default().matrix(Matrix::dynamic(
+                "${{ fromJSON(needs.setup.outputs.matrix) }}"
+            ))
+        );
+    }
+
+    #[test]
+    fn matrix_expression_entries() {
+        let yaml = r#"on:
+  push: {}
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        os:
+        - ubuntu-latest
+        include: ${{ fromJSON(needs.setup.outputs.include) }}
+        exclude:
+        - os: windows-latest
+"#;
+
+        let workflow = crate::Workflow::parse(yaml).unwrap();
+        let job = workflow.jobs.as_ref().unwrap().get("test").unwrap();
+        let matrix = job.strategy.as_ref().unwrap().matrix.as_ref().unwrap();
+
+        assert_eq!(
+            matrix.axes.get("include"),
+            Some(&Value::from("${{ fromJSON(needs.setup.outputs.include) }}"))
+        );
+        assert_eq!(matrix.exclude.len(), 1);
+        assert_eq!(workflow.to_string().unwrap(), yaml);
+    }
+
+    #[test]
+    fn matrix_unknown_axis() {
+        let mut matrix = Matrix::default();
+        matrix.add_axis("os", ["ubuntu-latest"]);
+
+        let result = matrix.add_exclude([("rust", "nightly")]);
+
+        assert!(matches!(result, Err(Error::UnknownMatrixAxis(axis)) if axis == "rust"));
+    }
+
+    #[test]
+    fn matrix_dynamic() {
+        let matrix = Context::needs()
+            .get("setup")
+            .outputs()
+            .get("matrix")
+            .from_json::<Value>();
+        let strategy = Strategy::default().matrix(Matrix::dynamic(matrix));
+
+        assert_eq!(
+            serde_yaml::to_string(&strategy).unwrap(),
+            "matrix: ${{ fromJSON(needs.setup.outputs.matrix) }}\n"
+        );
+    }
+}
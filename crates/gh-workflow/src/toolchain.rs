@@ -5,7 +5,7 @@ use std::fmt::{Display, Formatter};
 
 use derive_setters::Setters;
 
-use crate::{private, Input, RustFlags, Step, Use};
+use crate::{private, Input, Permissions, RustFlags, Step, Use};
 
 #[derive(Clone)]
 pub enum Version {
@@ -199,11 +199,9 @@ impl Toolchain {
 
 impl From<Toolchain> for Step<Use> {
     fn from(value: Toolchain) -> Self {
-        let mut step = Step::new("Setup Rust Toolchain").uses(
-            "actions-rust-lang",
-            "setup-rust-toolchain",
-            "v1",
-        );
+        let mut step = Step::new("Setup Rust Toolchain")
+            .uses("actions-rust-lang", "setup-rust-toolchain", "v1")
+            .require_permissions(Permissions::default());
 
         let toolchain = value
             .version
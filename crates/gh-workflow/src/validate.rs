@@ -0,0 +1,503 @@
+//!
+//! Semantic validation of workflows before they are written to disk.
+
+use std::fmt::{Display, Formatter};
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::{Action, ActionRuns, JobValue, StepValue, Workflow, WorkflowCallInputType};
+
+/// Represents a problem found while validating a workflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    /// The job id contains characters GitHub does not accept.
+    InvalidJobId { job: String },
+
+    /// A job depends on a job id that is not part of the workflow.
+    UnknownNeed { job: String, need: String },
+
+    /// A set of jobs depend on each other in a cycle.
+    DependencyCycle { jobs: Vec<String> },
+
+    /// A job that calls a reusable workflow also declares steps.
+    UsesJobWithSteps { job: String },
+
+    /// Two steps of the same job share the same `id`.
+    DuplicateStepId { job: String, id: String },
+
+    /// A step declares both `uses` and `run`.
+    StepWithUsesAndRun { job: String, step: usize },
+
+    /// A step declares neither `uses` nor `run`.
+    StepWithoutUsesOrRun { job: String, step: usize },
+
+    /// A step passes `with` inputs without using an action.
+    StepWithInputsWithoutUses { job: String, step: usize },
+
+    /// A job calls a reusable workflow without a required input.
+    MissingInput { job: String, input: String },
+
+    /// A job passes an input that the reusable workflow doesn't declare.
+    UnknownInput { job: String, input: String },
+
+    /// A job passes an input whose value doesn't match its declared type.
+    InputTypeMismatch {
+        job: String,
+        input: String,
+        expected: WorkflowCallInputType,
+    },
+
+    /// A job calls a reusable workflow without a required secret.
+    MissingSecret { job: String, secret: String },
+
+    /// A job passes a secret that the reusable workflow doesn't declare.
+    UnknownSecret { job: String, secret: String },
+
+    /// A `run` step of a composite action doesn't declare its `shell`.
+    CompositeStepWithoutShell { step: usize },
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::InvalidJobId { job } => write!(
+                f,
+                "job id `{job}` must start with a letter or `_` and contain only alphanumeric characters, `-` or `_`"
+            ),
+            Diagnostic::UnknownNeed { job, need } => {
+                write!(f, "job `{job}` needs unknown job `{need}`")
+            }
+            Diagnostic::DependencyCycle { jobs } => {
+                write!(f, "jobs form a dependency cycle: {}", jobs.join(" -> "))
+            }
+            Diagnostic::UsesJobWithSteps { job } => {
+                write!(f, "job `{job}` calls a reusable workflow and cannot have steps")
+            }
+            Diagnostic::DuplicateStepId { job, id } => {
+                write!(f, "job `{job}` has more than one step with id `{id}`")
+            }
+            Diagnostic::StepWithUsesAndRun { job, step } => {
+                write!(f, "step {step} of job `{job}` cannot have both `uses` and `run`")
+            }
+            Diagnostic::StepWithoutUsesOrRun { job, step } => {
+                write!(f, "step {step} of job `{job}` must have either `uses` or `run`")
+            }
+            Diagnostic::StepWithInputsWithoutUses { job, step } => {
+                write!(f, "step {step} of job `{job}` has `with` inputs but no `uses`")
+            }
+            Diagnostic::MissingInput { job, input } => {
+                write!(f, "job `{job}` doesn't pass required input `{input}`")
+            }
+            Diagnostic::UnknownInput { job, input } => {
+                write!(f, "job `{job}` passes unknown input `{input}`")
+            }
+            Diagnostic::InputTypeMismatch { job, input, expected } => {
+                write!(f, "job `{job}` passes input `{input}` that is not a {expected}")
+            }
+            Diagnostic::MissingSecret { job, secret } => {
+                write!(f, "job `{job}` doesn't pass required secret `{secret}`")
+            }
+            Diagnostic::UnknownSecret { job, secret } => {
+                write!(f, "job `{job}` passes unknown secret `{secret}`")
+            }
+            Diagnostic::CompositeStepWithoutShell { step } => {
+                write!(f, "step {step} of the composite action runs a command but has no `shell`")
+            }
+        }
+    }
+}
+
+impl Workflow {
+    /// Checks the workflow for problems that GitHub would only report once
+    /// the workflow runs, such as unknown `needs` or dependency cycles.
+    ///
+    /// Returns an empty list when no problems were found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let Some(jobs) = self.jobs.as_ref() else {
+            return diagnostics;
+        };
+
+        for (id, job) in jobs.0.iter() {
+            if !is_valid_job_id(id) {
+                diagnostics.push(Diagnostic::InvalidJobId { job: id.clone() });
+            }
+
+            for need in job.needs.iter().flatten() {
+                if !jobs.0.contains_key(need) {
+                    diagnostics
+                        .push(Diagnostic::UnknownNeed { job: id.clone(), need: need.clone() });
+                }
+            }
+
+            if let Some(steps) = job.steps.as_ref() {
+                if job.uses.is_some() {
+                    diagnostics.push(Diagnostic::UsesJobWithSteps { job: id.clone() });
+                }
+                validate_steps(id, steps, &mut diagnostics);
+            }
+            validate_call(id, job, &mut diagnostics);
+        }
+
+        diagnostics.extend(
+            find_cycles(&jobs.0)
+                .into_iter()
+                .map(|jobs| Diagnostic::DependencyCycle { jobs }),
+        );
+
+        diagnostics
+    }
+}
+
+impl Action {
+    /// Checks the action for problems that GitHub would only report once the
+    /// action runs. Its steps are checked like the steps of a job, and
+    /// reported under the name of the action.
+    ///
+    /// Returns an empty list when no problems were found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let ActionRuns::Composite { steps } = &self.runs;
+        let mut diagnostics = Vec::new();
+        validate_steps(&self.name, steps, &mut diagnostics);
+        diagnostics.extend(
+            steps
+                .iter()
+                .enumerate()
+                .filter(|(_, step)| step.run.is_some() && step.shell.is_none())
+                .map(|(step, _)| Diagnostic::CompositeStepWithoutShell { step }),
+        );
+        diagnostics
+    }
+}
+
+fn is_valid_job_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn validate_steps(job_id: &str, steps: &[StepValue], diagnostics: &mut Vec<Diagnostic>) {
+    let mut ids = IndexSet::new();
+    for (index, step) in steps.iter().enumerate() {
+        let job = job_id.to_string();
+
+        if let Some(id) = step.id.as_ref() {
+            if !ids.insert(id) {
+                diagnostics.push(Diagnostic::DuplicateStepId { job: job.clone(), id: id.clone() });
+            }
+        }
+
+        match (step.uses.is_some(), step.run.is_some()) {
+            (true, true) => {
+                diagnostics.push(Diagnostic::StepWithUsesAndRun { job: job.clone(), step: index })
+            }
+            (false, false) => {
+                diagnostics.push(Diagnostic::StepWithoutUsesOrRun { job: job.clone(), step: index })
+            }
+            _ => {}
+        }
+
+        if step.uses.is_none() && step.with.is_some() {
+            diagnostics.push(Diagnostic::StepWithInputsWithoutUses { job, step: index });
+        }
+    }
+}
+
+fn validate_call(job_id: &str, job: &JobValue, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(interface) = job.interface.as_ref() else {
+        return;
+    };
+    let job_id = job_id.to_string();
+    let with = job.with.as_ref().map(|with| &with.0);
+    let secrets = job.secrets.as_ref().map(|secrets| &secrets.0);
+
+    for (name, input) in interface.inputs.iter() {
+        match with.and_then(|with| with.get(name)) {
+            Some(value) if !input.input_type.accepts(value) => {
+                diagnostics.push(Diagnostic::InputTypeMismatch {
+                    job: job_id.clone(),
+                    input: name.clone(),
+                    expected: input.input_type,
+                })
+            }
+            None if input.required && input.default.is_none() => diagnostics
+                .push(Diagnostic::MissingInput { job: job_id.clone(), input: name.clone() }),
+            _ => {}
+        }
+    }
+
+    for name in with.into_iter().flat_map(|with| with.keys()) {
+        if !interface.inputs.contains_key(name) {
+            diagnostics.push(Diagnostic::UnknownInput { job: job_id.clone(), input: name.clone() });
+        }
+    }
+
+    for (name, secret) in interface.secrets.iter() {
+        if secret.required && !secrets.is_some_and(|secrets| secrets.contains_key(name)) {
+            diagnostics
+                .push(Diagnostic::MissingSecret { job: job_id.clone(), secret: name.clone() });
+        }
+    }
+
+    for name in secrets.into_iter().flat_map(|secrets| secrets.keys()) {
+        if !interface.secrets.contains_key(name) {
+            diagnostics
+                .push(Diagnostic::UnknownSecret { job: job_id.clone(), secret: name.clone() });
+        }
+    }
+}
+
+/// Finds every dependency cycle between jobs. Each cycle is reported once,
+/// starting from the job that appears first in the workflow.
+fn find_cycles(jobs: &IndexMap<String, JobValue>) -> Vec<Vec<String>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a>(
+        id: &'a str,
+        jobs: &'a IndexMap<String, JobValue>,
+        states: &mut IndexMap<&'a str, State>,
+        path: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        match states.get(id).copied().unwrap_or(State::Unvisited) {
+            State::Done => return,
+            State::Visiting => {
+                let start = path.iter().position(|p| *p == id).unwrap_or_default();
+                let mut cycle: Vec<String> = path[start..].iter().map(|p| p.to_string()).collect();
+                cycle.push(id.to_string());
+                cycles.push(cycle);
+                return;
+            }
+            State::Unvisited => {}
+        }
+
+        states.insert(id, State::Visiting);
+        path.push(id);
+        if let Some(job) = jobs.get(id) {
+            for need in job.needs.iter().flatten() {
+                if let Some((need, _)) = jobs.get_key_value(need.as_str()) {
+                    visit(need, jobs, states, path, cycles);
+                }
+            }
+        }
+        path.pop();
+        states.insert(id, State::Done);
+    }
+
+    let mut states = IndexMap::new();
+    let mut cycles = Vec::new();
+    for id in jobs.keys() {
+        visit(id, jobs, &mut states, &mut Vec::new(), &mut cycles);
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::ctx::Context;
+    use crate::generate::Generate;
+    use crate::{Job, Step, WorkflowCall, WorkflowCallInput, WorkflowCallSecret};
+
+    #[test]
+    fn valid_workflow() {
+        let workflow = Workflow::new("CI")
+            .add_job("build", Job::new("Build").add_step(Step::checkout()))
+            .add_job(
+                "test",
+                Job::new("Test")
+                    .add_need("build")
+                    .add_step(Step::new("Test").run("cargo test")),
+            );
+
+        assert_eq!(workflow.validate(), vec![]);
+    }
+
+    #[test]
+    fn unknown_needs_and_cycles() {
+        let workflow = Workflow::new("CI")
+            .add_job("a", Job::new("A").add_need("c").add_step(Step::checkout()))
+            .add_job("b", Job::new("B").add_need("a").add_step(Step::checkout()))
+            .add_job("c", Job::new("C").add_need("b").add_step(Step::checkout()))
+            .add_job(
+                "d",
+                Job::new("D").add_need("missing").add_step(Step::checkout()),
+            );
+
+        assert_eq!(
+            workflow.validate(),
+            vec![
+                Diagnostic::UnknownNeed { job: "d".into(), need: "missing".into() },
+                Diagnostic::DependencyCycle {
+                    jobs: vec!["a".into(), "c".into(), "b".into(), "a".into()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_steps() {
+        let mut both = Step::new("Both").run("echo hi");
+        both.value.uses = Some("actions/checkout@v5".into());
+
+        let workflow = Workflow::new("CI")
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .add_step(Step::new("One").run("echo 1").id("same"))
+                    .add_step(Step::new("Two").run("echo 2").id("same"))
+                    .add_step(both)
+                    .add_step(Step::new("Empty").run("").with(("key", "value"))),
+            )
+            .add_job("9-lives", Job::new("Invalid id").add_step(Step::checkout()));
+
+        let mut empty = Step::new("Nothing").run("");
+        empty.value.run = None;
+        let workflow = workflow.add_job("empty", Job::new("Empty").add_step(empty));
+
+        assert_eq!(
+            workflow.validate(),
+            vec![
+                Diagnostic::DuplicateStepId { job: "build".into(), id: "same".into() },
+                Diagnostic::StepWithUsesAndRun { job: "build".into(), step: 2 },
+                Diagnostic::StepWithInputsWithoutUses { job: "build".into(), step: 3 },
+                Diagnostic::InvalidJobId { job: "9-lives".into() },
+                Diagnostic::StepWithoutUsesOrRun { job: "empty".into(), step: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_job_with_steps() {
+        let job = JobValue {
+            uses: Some("owner/repo/.github/workflows/ci.yml@main".into()),
+            steps: Some(vec![Step::checkout().value]),
+            ..Default::default()
+        };
+
+        let mut workflow = Workflow::new("CI");
+        workflow.jobs = Some(crate::Jobs(
+            [("call".to_string(), job)].into_iter().collect(),
+        ));
+
+        assert_eq!(
+            workflow.validate(),
+            vec![Diagnostic::UsesJobWithSteps { job: "call".into() }]
+        );
+    }
+
+    #[test]
+    fn composite_steps() {
+        let mut both = Step::new("Cache").run("echo").shell("bash");
+        both.value.uses = Some("actions/cache@v4".to_string());
+        let action = Action::new("Build")
+            .add_step(Step::new("Build").run("cargo build").shell("bash"))
+            .add_step(Step::new("Test").run("cargo test"))
+            .add_step(Step::checkout())
+            .add_step(
+                Step::new("Lint")
+                    .run("cargo clippy")
+                    .shell("bash")
+                    .id("lint"),
+            )
+            .add_step(
+                Step::new("Format")
+                    .run("cargo fmt")
+                    .shell("bash")
+                    .id("lint"),
+            )
+            .add_step(both);
+
+        assert_eq!(
+            action.validate(),
+            vec![
+                Diagnostic::DuplicateStepId { job: "Build".into(), id: "lint".into() },
+                Diagnostic::StepWithUsesAndRun { job: "Build".into(), step: 5 },
+                Diagnostic::CompositeStepWithoutShell { step: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn call_checks_interface() {
+        let interface = WorkflowCall::default()
+            .add_input("version", WorkflowCallInput::string().required(true))
+            .add_input(
+                "dry-run",
+                WorkflowCallInput::boolean().required(true).default(false),
+            )
+            .add_input("retries", WorkflowCallInput::number())
+            .add_secret("token", WorkflowCallSecret::default().required(true));
+
+        let valid = Job::new("Release")
+            .call("./.github/workflows/release.yml", interface.clone())
+            .add_with("version", Context::github().ref_name().to_string())
+            .add_with("retries", 3)
+            .add_secret("token", Context::secrets().github_token().to_string());
+        let invalid = Job::new("Release")
+            .call("./.github/workflows/release.yml", interface)
+            .add_with("retries", "three")
+            .add_with("force", true)
+            .add_secret("npm-token", "${{ secrets.NPM_TOKEN }}");
+
+        let workflow = Workflow::new("CI")
+            .add_job("valid", valid)
+            .add_job("invalid", invalid);
+
+        assert_eq!(
+            workflow.validate(),
+            vec![
+                Diagnostic::MissingInput { job: "invalid".into(), input: "version".into() },
+                Diagnostic::InputTypeMismatch {
+                    job: "invalid".into(),
+                    input: "retries".into(),
+                    expected: WorkflowCallInputType::Number,
+                },
+                Diagnostic::UnknownInput { job: "invalid".into(), input: "force".into() },
+                Diagnostic::MissingSecret { job: "invalid".into(), secret: "token".into() },
+                Diagnostic::UnknownSecret { job: "invalid".into(), secret: "npm-token".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_local_workflow() {
+        fn uses(workflow: &Workflow) -> Option<&str> {
+            workflow.jobs.as_ref()?.get("release")?.uses.as_deref()
+        }
+
+        let release = Workflow::new("Release").add_event(
+            crate::Event::default().workflow_call(
+                WorkflowCall::default()
+                    .add_input("version", WorkflowCallInput::string().required(true)),
+            ),
+        );
+        let release = Generate::new(release).name("release.yml");
+
+        let job = Job::new("Release").uses_workflow(&release);
+        let workflow = Workflow::new("CI").add_job("release", job);
+        assert_eq!(uses(&workflow), Some("./.github/workflows/release.yml"));
+        assert_eq!(
+            workflow.validate(),
+            vec![Diagnostic::MissingInput { job: "release".into(), input: "version".into() }]
+        );
+
+        let job = Job::new("Release").uses_workflow(&release.workflows_dir("ci/workflows"));
+        let workflow = Workflow::new("CI").add_job("release", job);
+        assert_eq!(uses(&workflow), Some("./ci/workflows/release.yml"));
+
+        let job = Job::new("Release").uses_local(".github/workflows/release.yml");
+        let workflow = Workflow::new("CI").add_job("release", job);
+        assert_eq!(uses(&workflow), Some("./.github/workflows/release.yml"));
+        assert_eq!(workflow.validate(), vec![]);
+    }
+}
@@ -15,7 +15,7 @@ use crate::generate::Generate;
 use crate::job::Job;
 use crate::permissions::Permissions;
 use crate::secret::Secret;
-use crate::{Event, JobType, JobValue};
+use crate::{Event, JobType, JobValue, LintRule, YamlOptions};
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(transparent)]
@@ -95,6 +95,11 @@ pub struct Workflow {
     /// The maximum number of minutes a job can run before it is canceled.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_minutes: Option<u32>,
+
+    /// The lint rules suppressed for the whole workflow.
+    #[serde(skip)]
+    #[setters(skip)]
+    pub(crate) allowed_lints: Vec<LintRule>,
 }
 
 /// Represents an action that can be triggered by an event in the workflow.
@@ -121,6 +126,11 @@ impl Workflow {
         Ok(serde_yaml::to_string(self)?)
     }
 
+    /// Converts the `Workflow` to YAML, formatted with the given options.
+    pub fn to_string_with(&self, options: &YamlOptions) -> Result<String> {
+        crate::yaml::render_workflow(self, options)
+    }
+
     /// Adds a job to the workflow with the specified ID and job configuration.
     pub fn add_job<I: ToString, J: Into<Job<T>>, T: JobType>(mut self, id: I, job: J) -> Self {
         let key = id.to_string();
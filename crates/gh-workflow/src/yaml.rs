@@ -0,0 +1,475 @@
+//!
+//! Rendering of workflows to YAML with options that make generated files
+//! easier to review.
+
+use derive_setters::Setters;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::error::Result;
+
+/// How strings are quoted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Quotes strings only when YAML requires it.
+    #[default]
+    Minimal,
+    /// Quotes every string with single quotes.
+    Single,
+    /// Quotes every string with double quotes.
+    Double,
+}
+
+/// Options for rendering YAML.
+#[derive(Debug, Setters, Clone, PartialEq, Eq)]
+#[setters(strip_option, into)]
+pub struct YamlOptions {
+    /// Renders multi-line strings, such as `run` scripts, as block literals
+    /// (`|`) instead of quoted strings with escaped newlines.
+    pub block_literals: bool,
+
+    /// How single-line strings are quoted. Keys are only quoted when YAML
+    /// requires it.
+    pub quote_style: QuoteStyle,
+
+    /// Orders the keys of workflows, jobs and steps the way the GitHub docs
+    /// list them, starting with `name`, `on`, `permissions`, `env` and
+    /// `jobs`. Keys that aren't part of that order keep their relative order
+    /// and come last.
+    pub canonical_order: bool,
+
+    /// Separates jobs with a blank line.
+    pub blank_lines_between_jobs: bool,
+}
+
+impl Default for YamlOptions {
+    fn default() -> Self {
+        Self {
+            block_literals: true,
+            quote_style: QuoteStyle::Minimal,
+            canonical_order: true,
+            blank_lines_between_jobs: true,
+        }
+    }
+}
+
+const WORKFLOW_ORDER: &[&str] = &[
+    "name",
+    "run-name",
+    "on",
+    "permissions",
+    "env",
+    "defaults",
+    "concurrency",
+    "jobs",
+];
+
+const JOB_ORDER: &[&str] = &[
+    "name",
+    "permissions",
+    "needs",
+    "if",
+    "runs-on",
+    "environment",
+    "concurrency",
+    "outputs",
+    "env",
+    "defaults",
+    "timeout-minutes",
+    "strategy",
+    "continue-on-error",
+    "container",
+    "services",
+    "uses",
+    "with",
+    "secrets",
+    "steps",
+];
+
+const STEP_ORDER: &[&str] = &[
+    "id",
+    "if",
+    "name",
+    "uses",
+    "run",
+    "working-directory",
+    "shell",
+    "with",
+    "env",
+    "continue-on-error",
+    "timeout-minutes",
+];
+
+/// Renders a value to YAML.
+pub(crate) fn render<T: Serialize>(value: &T, options: &YamlOptions) -> Result<String> {
+    let value = serde_yaml::to_value(value)?;
+    emit(&value, options)
+}
+
+/// Renders a workflow to YAML, ordering its keys if asked.
+pub(crate) fn render_workflow<T: Serialize>(value: &T, options: &YamlOptions) -> Result<String> {
+    let mut value = serde_yaml::to_value(value)?;
+    if options.canonical_order {
+        order_workflow(&mut value);
+    }
+    emit(&value, options)
+}
+
+fn emit(value: &Value, options: &YamlOptions) -> Result<String> {
+    let mut out = String::new();
+    Renderer { options, out: &mut out }.document(value)?;
+    Ok(out)
+}
+
+fn order_workflow(workflow: &mut Value) {
+    let Value::Mapping(workflow) = workflow else {
+        return;
+    };
+    order(workflow, WORKFLOW_ORDER);
+
+    let Some(Value::Mapping(jobs)) = workflow.get_mut("jobs") else {
+        return;
+    };
+    for job in jobs.values_mut() {
+        let Value::Mapping(job) = job else {
+            continue;
+        };
+        order(job, JOB_ORDER);
+
+        if let Some(Value::Sequence(steps)) = job.get_mut("steps") {
+            for step in steps.iter_mut() {
+                if let Value::Mapping(step) = step {
+                    order(step, STEP_ORDER);
+                }
+            }
+        }
+    }
+}
+
+fn order(mapping: &mut Mapping, keys: &[&str]) {
+    let mut entries: Vec<_> = std::mem::take(mapping).into_iter().collect();
+    entries.sort_by_key(|(key, _)| {
+        key.as_str()
+            .and_then(|key| keys.iter().position(|k| *k == key))
+            .unwrap_or(keys.len())
+    });
+    mapping.extend(entries);
+}
+
+struct Renderer<'a> {
+    options: &'a YamlOptions,
+    out: &'a mut String,
+}
+
+impl Renderer<'_> {
+    fn document(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Mapping(mapping) if !mapping.is_empty() => self.mapping(mapping, 0, false, true),
+            Value::Sequence(sequence) if !sequence.is_empty() => self.sequence(sequence, 0, false),
+            value => {
+                self.scalar(value, 0)?;
+                self.out.push('\n');
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes a mapping whose keys are indented by `indent`. When `inline`
+    /// is set, the first key continues the current line, as in a sequence
+    /// item.
+    fn mapping(
+        &mut self,
+        mapping: &Mapping,
+        indent: usize,
+        inline: bool,
+        root: bool,
+    ) -> Result<()> {
+        for (index, (key, value)) in mapping.iter().enumerate() {
+            if index > 0 || !inline {
+                self.out.push_str(&" ".repeat(indent));
+            }
+            self.key(key)?;
+            self.out.push(':');
+
+            match value {
+                Value::Mapping(nested) if !nested.is_empty() => {
+                    self.out.push('\n');
+                    let is_jobs = root && key.as_str() == Some("jobs");
+                    if is_jobs && self.options.blank_lines_between_jobs {
+                        self.jobs(nested, indent + 2)?;
+                    } else {
+                        self.mapping(nested, indent + 2, false, false)?;
+                    }
+                }
+                Value::Sequence(nested) if !nested.is_empty() => {
+                    self.out.push('\n');
+                    self.sequence(nested, indent, false)?;
+                }
+                value => {
+                    self.out.push(' ');
+                    self.scalar(value, indent + 2)?;
+                    self.out.push('\n');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn jobs(&mut self, jobs: &Mapping, indent: usize) -> Result<()> {
+        for (index, (id, job)) in jobs.iter().enumerate() {
+            if index > 0 {
+                self.out.push('\n');
+            }
+            let mut single = Mapping::new();
+            single.insert(id.clone(), job.clone());
+            self.mapping(&single, indent, false, false)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a sequence whose dashes are indented by `indent`.
+    fn sequence(&mut self, sequence: &[Value], indent: usize, inline: bool) -> Result<()> {
+        for (index, item) in sequence.iter().enumerate() {
+            if index > 0 || !inline {
+                self.out.push_str(&" ".repeat(indent));
+            }
+            self.out.push_str("- ");
+
+            match item {
+                Value::Mapping(nested) if !nested.is_empty() => {
+                    self.mapping(nested, indent + 2, true, false)?;
+                }
+                Value::Sequence(nested) if !nested.is_empty() => {
+                    self.sequence(nested, indent + 2, true)?;
+                }
+                value => {
+                    self.scalar(value, indent + 2)?;
+                    self.out.push('\n');
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, key: &Value) -> Result<()> {
+        let key = serde_yaml::to_string(key)?;
+        self.out.push_str(key.trim_end_matches('\n'));
+        Ok(())
+    }
+
+    /// Writes a scalar, or an empty collection. Block literals are indented
+    /// by `indent`.
+    fn scalar(&mut self, value: &Value, indent: usize) -> Result<()> {
+        let Value::String(string) = value else {
+            let value = serde_yaml::to_string(value)?;
+            self.out.push_str(value.trim_end_matches('\n'));
+            return Ok(());
+        };
+
+        if string.contains('\n') {
+            if self.options.block_literals && is_literal_safe(string) {
+                self.literal(string, indent);
+            } else {
+                self.out.push_str(&double_quoted(string));
+            }
+            return Ok(());
+        }
+
+        match self.options.quote_style {
+            QuoteStyle::Minimal => {
+                let value = serde_yaml::to_string(value)?;
+                self.out.push_str(value.trim_end_matches('\n'));
+            }
+            QuoteStyle::Single if string.contains(is_line_separator) => {
+                self.out.push_str(&double_quoted(string))
+            }
+            QuoteStyle::Single => {
+                self.out.push('\'');
+                self.out.push_str(&string.replace('\'', "''"));
+                self.out.push('\'');
+            }
+            QuoteStyle::Double => self.out.push_str(&double_quoted(string)),
+        }
+        Ok(())
+    }
+
+    fn literal(&mut self, string: &str, indent: usize) {
+        let (chomping, body) = match string.strip_suffix('\n') {
+            None => ("-", string),
+            Some(body) if body.ends_with('\n') => ("+", body),
+            Some(body) => ("", body),
+        };
+
+        self.out.push('|');
+        self.out.push_str(chomping);
+        for line in body.split('\n') {
+            self.out.push('\n');
+            if !line.is_empty() {
+                self.out.push_str(&" ".repeat(indent));
+                self.out.push_str(line);
+            }
+        }
+    }
+}
+
+/// Quotes a string with double quotes, escaping the characters that can't
+/// appear in it as is.
+fn double_quoted(string: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() || is_line_separator(c) => {
+                quoted.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Checks that a string reads back the same from a block literal. Leading
+/// whitespace on the first line would need an explicit indentation, and
+/// control characters and Unicode line breaks can only be written escaped.
+fn is_literal_safe(string: &str) -> bool {
+    !string.starts_with([' ', '\t', '\n'])
+        && string
+            .chars()
+            .all(|c| c == '\n' || c == '\t' || !(c.is_control() || is_line_separator(c)))
+}
+
+/// Checks for the characters other than `\n` and `\r` that YAML reads as
+/// line breaks.
+fn is_line_separator(c: char) -> bool {
+    matches!(c, '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{Event, Job, Level, Permissions, Push, Step, Workflow};
+
+    fn workflow() -> Workflow {
+        Workflow::new("CI")
+            .add_env(("RUST_LOG", "debug"))
+            .on(Event::default().push(Push::default().add_branch("main")))
+            .permissions(Permissions::default().contents(Level::Read))
+            .add_job(
+                "build",
+                Job::new("Build")
+                    .add_step(Step::new("Build").run("cargo build\ncargo test\n"))
+                    .add_step(Step::new("Say").run("echo 'status: done'")),
+            )
+            .add_job(
+                "lint",
+                Job::new("Lint").add_step(Step::new("Lint").run("cargo clippy")),
+            )
+    }
+
+    #[test]
+    fn readable_workflow() {
+        let yaml = workflow().to_string_with(&YamlOptions::default()).unwrap();
+
+        assert_eq!(
+            yaml,
+            r#"name: CI
+on:
+  push:
+    branches:
+    - main
+permissions:
+  contents: read
+env:
+  RUST_LOG: debug
+jobs:
+  build:
+    name: Build
+    runs-on: ubuntu-latest
+    steps:
+    - name: Build
+      run: |
+        cargo build
+        cargo test
+    - name: Say
+      run: 'echo ''status: done'''
+
+  lint:
+    name: Lint
+    runs-on: ubuntu-latest
+    steps:
+    - name: Lint
+      run: cargo clippy
+"#
+        );
+        assert_eq!(
+            Workflow::parse(&yaml).unwrap().to_string().unwrap(),
+            workflow().to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn quote_styles_and_escaped_scripts() {
+        let options = YamlOptions::default()
+            .block_literals(false)
+            .canonical_order(false)
+            .blank_lines_between_jobs(false);
+        let job = Job::new("Build").add_step(Step::new("Build").run("cargo build\ncargo test"));
+        let workflow = Workflow::new("CI").add_job("build", job);
+
+        let double = workflow
+            .to_string_with(&options.clone().quote_style(QuoteStyle::Double))
+            .unwrap();
+        let single = workflow
+            .to_string_with(&options.quote_style(QuoteStyle::Single))
+            .unwrap();
+
+        assert_eq!(
+            double,
+            r#"name: "CI"
+jobs:
+  build:
+    name: "Build"
+    runs-on: "ubuntu-latest"
+    steps:
+    - name: "Build"
+      run: "cargo build\ncargo test"
+"#
+        );
+        assert!(single.starts_with("name: 'CI'\n"));
+        assert_eq!(
+            Workflow::parse(&double).unwrap().to_string().unwrap(),
+            workflow.to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn escapes_unicode_line_separators() {
+        for run in [
+            "a\u{2028}b\nc",
+            "a\u{2029}b\nc",
+            "a\u{85}b\nc",
+            "a\u{2028}b",
+        ] {
+            let job = Job::new("Build").add_step(Step::new("Build").run(run));
+            let workflow = Workflow::new("CI").add_job("build", job);
+
+            for quote_style in [QuoteStyle::Minimal, QuoteStyle::Single, QuoteStyle::Double] {
+                let options = YamlOptions::default().quote_style(quote_style);
+                let yaml = workflow.to_string_with(&options).unwrap();
+
+                assert_eq!(
+                    Workflow::parse(&yaml).unwrap().to_string().unwrap(),
+                    workflow.to_string().unwrap(),
+                    "{yaml}"
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use gh_workflow::Workflow;
+use gh_workflow::{Workflow, YamlOptions};
 use pretty_assertions::assert_eq;
 use serde_json::Value;
 
@@ -33,3 +33,48 @@ fn test_workflow_rust() {
     let (actual, expected) = split(include_str!("./fixtures/workflow-rust.yml"));
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_workflow_deploy() {
+    let (actual, expected) = split(include_str!("./fixtures/workflow-deploy.yml"));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_workflow_runners() {
+    let (actual, expected) = split(include_str!("./fixtures/workflow-runners.yml"));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_workflow_reusable() {
+    let (actual, expected) = split(include_str!("./fixtures/workflow-reusable.yml"));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_workflow_permissions() {
+    let (actual, expected) = split(include_str!("./fixtures/workflow-permissions.yml"));
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_workflow_formatted() {
+    let fixtures = [
+        include_str!("./fixtures/workflow-bench.yml"),
+        include_str!("./fixtures/workflow-ci.yml"),
+        include_str!("./fixtures/workflow-demo.yml"),
+        include_str!("./fixtures/workflow-rust.yml"),
+        include_str!("./fixtures/workflow-deploy.yml"),
+        include_str!("./fixtures/workflow-runners.yml"),
+        include_str!("./fixtures/workflow-reusable.yml"),
+        include_str!("./fixtures/workflow-permissions.yml"),
+    ];
+
+    for content in fixtures {
+        let parsed = Workflow::parse(content).unwrap();
+        let formatted = parsed.to_string_with(&YamlOptions::default()).unwrap();
+        let expected = serde_yaml::from_str::<Value>(content).unwrap();
+        assert_eq!(serde_yaml::from_str::<Value>(&formatted).unwrap(), expected);
+    }
+}